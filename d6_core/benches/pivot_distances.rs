@@ -0,0 +1,59 @@
+// Builds trajectories from motions derived from a cold and from a warmed
+// motion, reporting the time taken and the pivot distances evaluated.
+// Run with `cargo bench --features metrics`.
+use std::time::Instant;
+
+use d6_core::pga::Pivot;
+use d6_core::pga::PivotalMotion;
+use d6_core::pga::PivotalMotionTrajectory;
+use glam::Mat3;
+use glam::Vec3;
+
+const MOTION_COUNT: usize = 10000;
+
+fn pivotal_motion() -> PivotalMotion {
+    PivotalMotion::from_pivots(Vec::from([
+        Pivot::from_translation_vector(Vec3::Y),
+        Pivot::from_plucker(std::f32::consts::FRAC_PI_2 * Vec3::X, Vec3::Z),
+        Pivot::from_plucker(std::f32::consts::FRAC_PI_2 * Vec3::Y, Vec3::Z),
+    ]))
+    .pivotal_local_transform(Pivot::from_translation_vector(Vec3::X))
+}
+
+fn report(name: &str, pivotal_motions: Vec<PivotalMotion>) {
+    let start = Instant::now();
+    pivotal_motions.iter().cloned().for_each(|pivotal_motion| {
+        std::hint::black_box(PivotalMotionTrajectory::from_pivotal_motions(Vec::from([
+            pivotal_motion,
+        ])));
+    });
+    let elapsed = start.elapsed();
+    let distance_evaluations: usize = pivotal_motions
+        .iter()
+        .map(|pivotal_motion| {
+            pivotal_motion.eval(0.0);
+            pivotal_motion.distance_evaluations()
+        })
+        .sum();
+    println!(
+        "{name}: {MOTION_COUNT} trajectories in {elapsed:?}, {distance_evaluations} distance evaluations"
+    );
+}
+
+fn main() {
+    let rotation = |k: usize| Mat3::from_rotation_z(k as f32);
+    report(
+        "cold",
+        (0..MOTION_COUNT)
+            .map(|k| pivotal_motion().rotate_global(rotation(k)))
+            .collect(),
+    );
+    let warm_pivotal_motion = pivotal_motion();
+    warm_pivotal_motion.eval(0.0);
+    report(
+        "warm",
+        (0..MOTION_COUNT)
+            .map(|k| warm_pivotal_motion.clone().rotate_global(rotation(k)))
+            .collect(),
+    );
+}
@@ -1,9 +1,13 @@
 // Notation: https://en.wikipedia.org/wiki/Dihedral_group
 
+use std::collections::HashSet;
+
 use glam::Mat3;
+use glam::Quat;
 use glam::Vec3;
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub enum D6 {
     R0,
     R1,
@@ -19,28 +23,204 @@ pub enum D6 {
     S5,
 }
 
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for D6 {
+    type Parameters = ();
+    type Strategy = proptest::sample::Select<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        proptest::sample::select(Self::iter_all().collect::<Vec<_>>())
+    }
+}
+
+#[rustfmt::skip]
+const MULTIPLICATION_TABLE: [[D6; 12]; 12] = [
+    [D6::R0, D6::R1, D6::R2, D6::R3, D6::R4, D6::R5, D6::S0, D6::S1, D6::S2, D6::S3, D6::S4, D6::S5],
+    [D6::R1, D6::R2, D6::R3, D6::R4, D6::R5, D6::R0, D6::S1, D6::S2, D6::S3, D6::S4, D6::S5, D6::S0],
+    [D6::R2, D6::R3, D6::R4, D6::R5, D6::R0, D6::R1, D6::S2, D6::S3, D6::S4, D6::S5, D6::S0, D6::S1],
+    [D6::R3, D6::R4, D6::R5, D6::R0, D6::R1, D6::R2, D6::S3, D6::S4, D6::S5, D6::S0, D6::S1, D6::S2],
+    [D6::R4, D6::R5, D6::R0, D6::R1, D6::R2, D6::R3, D6::S4, D6::S5, D6::S0, D6::S1, D6::S2, D6::S3],
+    [D6::R5, D6::R0, D6::R1, D6::R2, D6::R3, D6::R4, D6::S5, D6::S0, D6::S1, D6::S2, D6::S3, D6::S4],
+    [D6::S0, D6::S5, D6::S4, D6::S3, D6::S2, D6::S1, D6::R0, D6::R5, D6::R4, D6::R3, D6::R2, D6::R1],
+    [D6::S1, D6::S0, D6::S5, D6::S4, D6::S3, D6::S2, D6::R1, D6::R0, D6::R5, D6::R4, D6::R3, D6::R2],
+    [D6::S2, D6::S1, D6::S0, D6::S5, D6::S4, D6::S3, D6::R2, D6::R1, D6::R0, D6::R5, D6::R4, D6::R3],
+    [D6::S3, D6::S2, D6::S1, D6::S0, D6::S5, D6::S4, D6::R3, D6::R2, D6::R1, D6::R0, D6::R5, D6::R4],
+    [D6::S4, D6::S3, D6::S2, D6::S1, D6::S0, D6::S5, D6::R4, D6::R3, D6::R2, D6::R1, D6::R0, D6::R5],
+    [D6::S5, D6::S4, D6::S3, D6::S2, D6::S1, D6::S0, D6::R5, D6::R4, D6::R3, D6::R2, D6::R1, D6::R0],
+];
+
 impl std::ops::Mul<Self> for D6 {
     type Output = Self;
     fn mul(self, rhs: Self) -> Self::Output {
-        #[rustfmt::skip]
-        const MULTIPLICATION_TABLE: [[D6; 12]; 12] = [
-            [D6::R0, D6::R1, D6::R2, D6::R3, D6::R4, D6::R5, D6::S0, D6::S1, D6::S2, D6::S3, D6::S4, D6::S5],
-            [D6::R1, D6::R2, D6::R3, D6::R4, D6::R5, D6::R0, D6::S1, D6::S2, D6::S3, D6::S4, D6::S5, D6::S0],
-            [D6::R2, D6::R3, D6::R4, D6::R5, D6::R0, D6::R1, D6::S2, D6::S3, D6::S4, D6::S5, D6::S0, D6::S1],
-            [D6::R3, D6::R4, D6::R5, D6::R0, D6::R1, D6::R2, D6::S3, D6::S4, D6::S5, D6::S0, D6::S1, D6::S2],
-            [D6::R4, D6::R5, D6::R0, D6::R1, D6::R2, D6::R3, D6::S4, D6::S5, D6::S0, D6::S1, D6::S2, D6::S3],
-            [D6::R5, D6::R0, D6::R1, D6::R2, D6::R3, D6::R4, D6::S5, D6::S0, D6::S1, D6::S2, D6::S3, D6::S4],
-            [D6::S0, D6::S5, D6::S4, D6::S3, D6::S2, D6::S1, D6::R0, D6::R5, D6::R4, D6::R3, D6::R2, D6::R1],
-            [D6::S1, D6::S0, D6::S5, D6::S4, D6::S3, D6::S2, D6::R1, D6::R0, D6::R5, D6::R4, D6::R3, D6::R2],
-            [D6::S2, D6::S1, D6::S0, D6::S5, D6::S4, D6::S3, D6::R2, D6::R1, D6::R0, D6::R5, D6::R4, D6::R3],
-            [D6::S3, D6::S2, D6::S1, D6::S0, D6::S5, D6::S4, D6::R3, D6::R2, D6::R1, D6::R0, D6::R5, D6::R4],
-            [D6::S4, D6::S3, D6::S2, D6::S1, D6::S0, D6::S5, D6::R4, D6::R3, D6::R2, D6::R1, D6::R0, D6::R5],
-            [D6::S5, D6::S4, D6::S3, D6::S2, D6::S1, D6::S0, D6::R5, D6::R4, D6::R3, D6::R2, D6::R1, D6::R0],
-        ];
         MULTIPLICATION_TABLE[self as usize][rhs as usize] as Self
     }
 }
 
+lazy_static::lazy_static! {
+    static ref ACTION_MATRICES: [Mat3; 12] = [
+        D6::R0, D6::R1, D6::R2, D6::R3, D6::R4, D6::R5,
+        D6::S0, D6::S1, D6::S2, D6::S3, D6::S4, D6::S5,
+    ]
+    .map(D6::compute_mat3);
+}
+
+impl D6 {
+    // Row `g`, column `h` holds `g * h`, indexed in declaration order.
+    pub fn cayley_table() -> [[Self; 12]; 12] {
+        MULTIPLICATION_TABLE
+    }
+
+    pub fn identity() -> Self {
+        Self::R0
+    }
+
+    pub fn inverse(self) -> Self {
+        match self {
+            Self::R0 => Self::R0,
+            Self::R1 => Self::R5,
+            Self::R2 => Self::R4,
+            Self::R3 => Self::R3,
+            Self::R4 => Self::R2,
+            Self::R5 => Self::R1,
+            reflection => reflection,
+        }
+    }
+
+    // Every element has an order dividing 6, so the exponent is taken modulo 6,
+    // which also covers negative exponents.
+    pub fn pow(self, n: i32) -> Self {
+        (0..n.rem_euclid(6)).fold(Self::identity(), |power, _| power * self)
+    }
+
+    pub fn order(self) -> u8 {
+        (1..=6).find(|&k| self.pow(k) == Self::identity()).unwrap() as u8
+    }
+
+    pub fn iter_all() -> impl Iterator<Item = Self> {
+        [
+            Self::R0,
+            Self::R1,
+            Self::R2,
+            Self::R3,
+            Self::R4,
+            Self::R5,
+            Self::S0,
+            Self::S1,
+            Self::S2,
+            Self::S3,
+            Self::S4,
+            Self::S5,
+        ]
+        .into_iter()
+    }
+
+    // The rotations fix the axis (1, 1, 1), while the reflections reverse it.
+    pub fn is_rotation(self) -> bool {
+        matches!(
+            self,
+            Self::R0 | Self::R1 | Self::R2 | Self::R3 | Self::R4 | Self::R5
+        )
+    }
+
+    // The matrix the element acts on space by, as used by the movement engine.
+    pub fn into_mat3(self) -> Mat3 {
+        ACTION_MATRICES[self as usize]
+    }
+
+    // Odd powers of the rotation and odd reflections also make a half turn
+    // about (1, 1, 1).
+    fn compute_mat3(self) -> Mat3 {
+        const REFLECTION_MATRIX: Mat3 = Mat3::from_cols_array_2d(&[
+            [-1.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0],
+            [2.0 / 3.0, -1.0 / 3.0, 2.0 / 3.0],
+            [2.0 / 3.0, 2.0 / 3.0, -1.0 / 3.0],
+        ]);
+        let (axis_system, reflect) = match self {
+            Self::R0 => (AxisSystem::PosXPosYPosZ, false),
+            Self::R1 => (AxisSystem::PosZPosXPosY, true),
+            Self::R2 => (AxisSystem::PosYPosZPosX, false),
+            Self::R3 => (AxisSystem::PosXPosYPosZ, true),
+            Self::R4 => (AxisSystem::PosZPosXPosY, false),
+            Self::R5 => (AxisSystem::PosYPosZPosX, true),
+            Self::S0 => (AxisSystem::NegYNegXNegZ, false),
+            Self::S1 => (AxisSystem::NegXNegZNegY, true),
+            Self::S2 => (AxisSystem::NegZNegYNegX, false),
+            Self::S3 => (AxisSystem::NegYNegXNegZ, true),
+            Self::S4 => (AxisSystem::NegXNegZNegY, false),
+            Self::S5 => (AxisSystem::NegZNegYNegX, true),
+        };
+        reflect
+            .then_some(REFLECTION_MATRIX)
+            .unwrap_or(Mat3::IDENTITY)
+            * axis_system.into_mat3()
+    }
+
+    pub fn act_on_direction(self, direction: Direction) -> Direction {
+        #[rustfmt::skip]
+        const DIRECTION_ACTION_TABLE: [[Direction; 6]; 12] = {
+            use Direction as Z6;
+            [
+                [Z6::PosX, Z6::PosY, Z6::PosZ, Z6::NegX, Z6::NegY, Z6::NegZ],
+                [Z6::NegZ, Z6::NegX, Z6::NegY, Z6::PosZ, Z6::PosX, Z6::PosY],
+                [Z6::PosY, Z6::PosZ, Z6::PosX, Z6::NegY, Z6::NegZ, Z6::NegX],
+                [Z6::NegX, Z6::NegY, Z6::NegZ, Z6::PosX, Z6::PosY, Z6::PosZ],
+                [Z6::PosZ, Z6::PosX, Z6::PosY, Z6::NegZ, Z6::NegX, Z6::NegY],
+                [Z6::NegY, Z6::NegZ, Z6::NegX, Z6::PosY, Z6::PosZ, Z6::PosX],
+                [Z6::NegY, Z6::NegX, Z6::NegZ, Z6::PosY, Z6::PosX, Z6::PosZ],
+                [Z6::PosX, Z6::PosZ, Z6::PosY, Z6::NegX, Z6::NegZ, Z6::NegY],
+                [Z6::NegZ, Z6::NegY, Z6::NegX, Z6::PosZ, Z6::PosY, Z6::PosX],
+                [Z6::PosY, Z6::PosX, Z6::PosZ, Z6::NegY, Z6::NegX, Z6::NegZ],
+                [Z6::NegX, Z6::NegZ, Z6::NegY, Z6::PosX, Z6::PosZ, Z6::PosY],
+                [Z6::PosZ, Z6::PosY, Z6::PosX, Z6::NegZ, Z6::NegY, Z6::NegX],
+            ]
+        };
+        DIRECTION_ACTION_TABLE[self as usize][direction as usize]
+    }
+}
+
+impl std::fmt::Display for D6 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseD6Error(String);
+
+impl std::fmt::Display for ParseD6Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unknown D6 element `{}`, expected one of R0..R5, S0..S5, e or id",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseD6Error {}
+
+impl std::str::FromStr for D6 {
+    type Err = ParseD6Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "R0" | "E" | "ID" => Ok(Self::R0),
+            "R1" => Ok(Self::R1),
+            "R2" => Ok(Self::R2),
+            "R3" => Ok(Self::R3),
+            "R4" => Ok(Self::R4),
+            "R5" => Ok(Self::R5),
+            "S0" => Ok(Self::S0),
+            "S1" => Ok(Self::S1),
+            "S2" => Ok(Self::S2),
+            "S3" => Ok(Self::S3),
+            "S4" => Ok(Self::S4),
+            "S5" => Ok(Self::S5),
+            _ => Err(ParseD6Error(s.to_string())),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum Direction {
     PosX,
@@ -51,6 +231,25 @@ pub enum Direction {
     NegZ,
 }
 
+const DIRECTIONS: [Direction; 6] = [
+    Direction::PosX,
+    Direction::PosY,
+    Direction::PosZ,
+    Direction::NegX,
+    Direction::NegY,
+    Direction::NegZ,
+];
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for Direction {
+    type Parameters = ();
+    type Strategy = proptest::sample::Select<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        proptest::sample::select(&DIRECTIONS[..])
+    }
+}
+
 impl Direction {
     pub fn into_vec3(self) -> Vec3 {
         match self {
@@ -62,6 +261,41 @@ impl Direction {
             Self::NegZ => Vec3::NEG_Z,
         }
     }
+
+    // Variants are ordered so that the index modulo 3 is the axis and the
+    // first three are the positive ones.
+    pub fn is_parallel(self, other: Self) -> bool {
+        self as usize % 3 == other as usize % 3
+    }
+
+    pub fn is_perpendicular(self, other: Self) -> bool {
+        !self.is_parallel(other)
+    }
+
+    pub fn dot_sign(self, other: Self) -> i8 {
+        match (self.is_parallel(other), self == other) {
+            (false, _) => 0,
+            (true, true) => 1,
+            (true, false) => -1,
+        }
+    }
+
+    // A left action: `direction.act(a * b) == direction.act(b).act(a)`.
+    pub fn act(self, action: D6) -> Self {
+        action.act_on_direction(self)
+    }
+
+    pub fn orbit(self) -> HashSet<Self> {
+        D6::iter_all().map(|action| self.act(action)).collect()
+    }
+}
+
+// The parity of the axis permutation of an axis system. The 12 even systems are the
+// rotations of a tetrahedron inscribed in the cube, and the 12 odd ones their coset.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum AxisParity {
+    Even,
+    Odd,
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
@@ -94,6 +328,10 @@ pub enum AxisSystem {
 }
 
 impl AxisSystem {
+    pub fn iter_all() -> impl Iterator<Item = Self> {
+        AXIS_SYSTEMS.into_iter()
+    }
+
     pub fn into_triplet(self) -> (Direction, Direction, Direction) {
         match self {
             Self::PosXPosYPosZ => (Direction::PosX, Direction::PosY, Direction::PosZ),
@@ -123,6 +361,25 @@ impl AxisSystem {
         }
     }
 
+    pub fn parity(self) -> AxisParity {
+        let (x_direction, y_direction, z_direction) = self.into_triplet();
+        let negative_count = [x_direction, y_direction, z_direction]
+            .into_iter()
+            .filter(|direction| {
+                matches!(
+                    direction,
+                    Direction::NegX | Direction::NegY | Direction::NegZ
+                )
+            })
+            .count();
+        // A rotation flips an even number of signs exactly when it permutes the axes evenly.
+        if negative_count % 2 == 0 {
+            AxisParity::Even
+        } else {
+            AxisParity::Odd
+        }
+    }
+
     pub fn into_mat3(self) -> Mat3 {
         let (x_direction, y_direction, z_direction) = self.into_triplet();
         Mat3::from_cols(
@@ -131,4 +388,444 @@ impl AxisSystem {
             z_direction.into_vec3(),
         )
     }
+
+    fn act_on_direction(self, direction: Direction) -> Direction {
+        let (x_direction, y_direction, z_direction) = self.into_triplet();
+        let image = [x_direction, y_direction, z_direction][direction as usize % 3];
+        if direction as usize >= 3 {
+            DIRECTIONS[(image as usize + 3) % 6]
+        } else {
+            image
+        }
+    }
+
+    // Actions move the grid axes as in `act_on_direction`. Only `R0`, `R2`, `R4`,
+    // `S0`, `S2` and `S4` do so by rotations; the others are improper, which no
+    // axis system is, so they give `None`.
+    pub fn act(self, action: D6) -> Option<Self> {
+        let (x_direction, y_direction, z_direction) = self.into_triplet();
+        let triplet = (
+            x_direction.act(action),
+            y_direction.act(action),
+            z_direction.act(action),
+        );
+        AXIS_SYSTEMS
+            .into_iter()
+            .find(|axis_system| axis_system.into_triplet() == triplet)
+    }
+
+    // Snaps each column to a signed basis axis, so matrices that are not
+    // rotations permuting the axes yield `None`.
+    pub fn from_mat3(matrix: Mat3) -> Option<Self> {
+        const EPSILON: f32 = 1e-4;
+        let [x_direction, y_direction, z_direction] = [matrix.x_axis, matrix.y_axis, matrix.z_axis]
+            .map(|column| {
+                DIRECTIONS
+                    .into_iter()
+                    .find(|direction| column.abs_diff_eq(direction.into_vec3(), EPSILON))
+            });
+        let triplet = (x_direction?, y_direction?, z_direction?);
+        AXIS_SYSTEMS
+            .into_iter()
+            .find(|axis_system| axis_system.into_triplet() == triplet)
+    }
+
+    // Quaternions only represent proper rotations. Every variant currently is
+    // one, but the check guards against reflected systems being added.
+    pub fn into_quat(self) -> Option<Quat> {
+        let matrix = self.into_mat3();
+        (matrix.determinant() > 0.0).then(|| Quat::from_mat3(&matrix))
+    }
+}
+
+impl std::ops::Mul<Self> for AxisSystem {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        let (x_direction, y_direction, z_direction) = rhs.into_triplet();
+        let triplet = (
+            self.act_on_direction(x_direction),
+            self.act_on_direction(y_direction),
+            self.act_on_direction(z_direction),
+        );
+        AXIS_SYSTEMS
+            .into_iter()
+            .find(|axis_system| axis_system.into_triplet() == triplet)
+            .unwrap()
+    }
+}
+
+#[rustfmt::skip]
+const AXIS_SYSTEMS: [AxisSystem; 24] = [
+    AxisSystem::PosXPosYPosZ, AxisSystem::NegXNegYPosZ, AxisSystem::PosXNegYNegZ, AxisSystem::NegXPosYNegZ,
+    AxisSystem::PosXPosZNegY, AxisSystem::NegXNegZNegY, AxisSystem::PosXNegZPosY, AxisSystem::NegXPosZPosY,
+    AxisSystem::PosYPosZPosX, AxisSystem::NegYNegZPosX, AxisSystem::PosYNegZNegX, AxisSystem::NegYPosZNegX,
+    AxisSystem::PosYPosXNegZ, AxisSystem::NegYNegXNegZ, AxisSystem::PosYNegXPosZ, AxisSystem::NegYPosXPosZ,
+    AxisSystem::PosZPosXPosY, AxisSystem::NegZNegXPosY, AxisSystem::PosZNegXNegY, AxisSystem::NegZPosXNegY,
+    AxisSystem::PosZPosYNegX, AxisSystem::NegZNegYNegX, AxisSystem::PosZNegYPosX, AxisSystem::NegZPosYPosX,
+];
+
+impl std::fmt::Display for AxisSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseAxisSystemError(String);
+
+impl std::fmt::Display for ParseAxisSystemError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unknown axis system `{}`, expected a rotation such as PosXPosYPosZ or +X+Y+Z",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseAxisSystemError {}
+
+// Accepts the variant names as well as the signed shorthand `+X-Y-Z`, in any
+// case. Triplets that are not rotations are rejected.
+impl std::str::FromStr for AxisSystem {
+    type Err = ParseAxisSystemError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized: Vec<_> = s
+            .trim()
+            .to_ascii_uppercase()
+            .replace("POS", "+")
+            .replace("NEG", "-")
+            .chars()
+            .collect();
+        let directions: Option<Vec<_>> = normalized
+            .chunks(2)
+            .map(|chunk| match chunk {
+                ['+', 'X'] => Some(Direction::PosX),
+                ['+', 'Y'] => Some(Direction::PosY),
+                ['+', 'Z'] => Some(Direction::PosZ),
+                ['-', 'X'] => Some(Direction::NegX),
+                ['-', 'Y'] => Some(Direction::NegY),
+                ['-', 'Z'] => Some(Direction::NegZ),
+                _ => None,
+            })
+            .collect();
+        directions
+            .and_then(|directions| match directions[..] {
+                [x_direction, y_direction, z_direction] => {
+                    AXIS_SYSTEMS.into_iter().find(|axis_system| {
+                        axis_system.into_triplet() == (x_direction, y_direction, z_direction)
+                    })
+                }
+                _ => None,
+            })
+            .ok_or_else(|| ParseAxisSystemError(s.to_string()))
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for AxisSystem {
+    type Parameters = ();
+    type Strategy = proptest::sample::Select<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        proptest::sample::select(&AXIS_SYSTEMS[..])
+    }
+}
+
+#[test]
+fn test_axis_parity() {
+    // Every axis system is a proper rotation, so the split is not by determinant.
+    assert!(AXIS_SYSTEMS
+        .iter()
+        .all(|axis_system| (axis_system.into_mat3().determinant() - 1.0).abs() < 1e-6));
+    let even_axis_systems: Vec<_> = AXIS_SYSTEMS
+        .into_iter()
+        .filter(|axis_system| axis_system.parity() == AxisParity::Even)
+        .collect();
+    assert_eq!(even_axis_systems.len(), 12);
+    // The parity agrees with that of the underlying permutation of axes.
+    assert!(AXIS_SYSTEMS.into_iter().all(|axis_system| {
+        let (x_direction, y_direction, z_direction) = axis_system.into_triplet();
+        let axes = [x_direction, y_direction, z_direction].map(|direction| direction as usize % 3);
+        let is_cyclic = [[0, 1, 2], [1, 2, 0], [2, 0, 1]].contains(&axes);
+        is_cyclic == (axis_system.parity() == AxisParity::Even)
+    }));
+    // The even systems form a subgroup: it is closed under composition.
+    assert!(even_axis_systems.iter().all(|a| {
+        even_axis_systems.iter().all(|b| {
+            let product = a.into_mat3() * b.into_mat3();
+            even_axis_systems
+                .iter()
+                .any(|c| c.into_mat3().abs_diff_eq(product, 1e-6))
+        })
+    }));
+}
+
+#[test]
+fn test_d6_from_str() {
+    assert_eq!("E".parse(), Ok(D6::R0));
+    assert_eq!("id".parse(), Ok(D6::R0));
+    assert_eq!("r0".parse(), Ok(D6::R0));
+    assert_eq!("s3".parse(), Ok(D6::S3));
+    let error = "R6".parse::<D6>().unwrap_err();
+    assert!(error.to_string().contains("`R6`"));
+    D6::iter_all().for_each(|action| assert_eq!(action.to_string().parse(), Ok(action)));
+    assert_eq!(D6::R3.to_string(), "R3");
+}
+
+#[test]
+fn test_axis_system_from_str() {
+    AXIS_SYSTEMS.into_iter().for_each(|axis_system| {
+        assert_eq!(axis_system.to_string().parse(), Ok(axis_system));
+    });
+    assert_eq!("+x-y-z".parse(), Ok(AxisSystem::PosXNegYNegZ));
+    assert_eq!(" negZposYposX ".parse(), Ok(AxisSystem::NegZPosYPosX));
+    ["+X+Y-Z", "+X+X+Z", "+X+Y", "PosXPosYPosZPosX", ""]
+        .into_iter()
+        .for_each(|s| assert!(s.parse::<AxisSystem>().is_err()));
+}
+
+#[test]
+fn test_direction_orbit() {
+    let orbit = Direction::PosX.orbit();
+    assert_eq!(orbit.len(), 6);
+    assert_eq!(Direction::NegZ.orbit(), orbit);
+}
+
+#[test]
+fn test_into_quat() {
+    AXIS_SYSTEMS.into_iter().for_each(|axis_system| {
+        let quat = axis_system.into_quat().unwrap();
+        assert!(Mat3::from_quat(quat).abs_diff_eq(axis_system.into_mat3(), 1e-6));
+    });
+}
+
+#[test]
+fn test_direction_dot_sign() {
+    assert!(Direction::PosX.is_perpendicular(Direction::PosY));
+    assert!(Direction::PosX.is_parallel(Direction::NegX));
+    assert!(!Direction::PosZ.is_perpendicular(Direction::PosZ));
+    DIRECTIONS.into_iter().for_each(|direction| {
+        DIRECTIONS.into_iter().for_each(|other| {
+            let dot = direction.into_vec3().dot(other.into_vec3());
+            assert_eq!(direction.dot_sign(other), dot as i8);
+            assert_eq!(direction.is_perpendicular(other), dot == 0.0);
+        });
+    });
+}
+
+#[test]
+fn test_cayley_table() {
+    let table = D6::cayley_table();
+    D6::iter_all().for_each(|g| {
+        assert_eq!(table[D6::R0 as usize][g as usize], g);
+        assert_eq!(table[g as usize][D6::R0 as usize], g);
+        assert_eq!(
+            D6::iter_all()
+                .filter(|&h| table[g as usize][h as usize] == D6::R0)
+                .count(),
+            1
+        );
+        D6::iter_all().for_each(|h| {
+            assert_eq!(table[g as usize][h as usize], g * h);
+            D6::iter_all().for_each(|k| {
+                assert_eq!((g * h) * k, g * (h * k));
+            });
+        });
+    });
+    // Every row is a permutation of the group.
+    assert!(table
+        .iter()
+        .all(|row| row.iter().collect::<HashSet<_>>().len() == 12));
+}
+
+#[test]
+fn test_d6_inverse() {
+    D6::iter_all().for_each(|g| {
+        assert_eq!(g * g.inverse(), D6::identity());
+        assert_eq!(g.inverse() * g, D6::identity());
+        assert_eq!(g.pow(-1), g.inverse());
+        assert_eq!(g.pow(0), D6::identity());
+        assert_eq!(g.pow(3), g * g * g);
+        assert_eq!(g.pow(-2), g.inverse() * g.inverse());
+        assert_eq!(g.pow(g.order() as i32), D6::identity());
+    });
+    assert_eq!(
+        D6::iter_all().map(D6::order).collect::<Vec<_>>(),
+        [1, 6, 3, 2, 3, 6, 2, 2, 2, 2, 2, 2]
+    );
+}
+
+#[test]
+fn test_axis_system_from_mat3() {
+    AXIS_SYSTEMS.into_iter().for_each(|axis_system| {
+        assert_eq!(
+            AxisSystem::from_mat3(axis_system.into_mat3()),
+            Some(axis_system)
+        );
+        assert_eq!(
+            AxisSystem::from_mat3(axis_system.into_mat3() * (1.0 + 1e-6)),
+            Some(axis_system)
+        );
+    });
+    assert_eq!(
+        AxisSystem::from_mat3(Mat3::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+        None
+    );
+    assert_eq!(
+        AxisSystem::from_mat3(Mat3::from_diagonal(Vec3::new(2.0, 1.0, 1.0))),
+        None
+    );
+    // A reflection permutes the axes, but is none of the rotations.
+    assert_eq!(
+        AxisSystem::from_mat3(Mat3::from_diagonal(Vec3::new(-1.0, 1.0, 1.0))),
+        None
+    );
+    assert_eq!(AxisSystem::from_mat3(Mat3::ZERO), None);
+}
+
+#[test]
+fn test_axis_system_mul() {
+    AXIS_SYSTEMS.into_iter().for_each(|a| {
+        AXIS_SYSTEMS.into_iter().for_each(|b| {
+            assert_eq!((a * b).into_mat3(), a.into_mat3() * b.into_mat3());
+            AXIS_SYSTEMS.into_iter().for_each(|c| {
+                assert_eq!((a * b) * c, a * (b * c));
+            });
+        });
+    });
+    D6::iter_all().for_each(|action| {
+        let action_matrix = Mat3::from_cols(
+            action.act_on_direction(Direction::PosX).into_vec3(),
+            action.act_on_direction(Direction::PosY).into_vec3(),
+            action.act_on_direction(Direction::PosZ).into_vec3(),
+        );
+        let is_proper = action_matrix.determinant() > 0.0;
+        AXIS_SYSTEMS.into_iter().for_each(|axis_system| {
+            assert_eq!(
+                axis_system.act(action),
+                is_proper.then(
+                    || AxisSystem::from_mat3(action_matrix * axis_system.into_mat3()).unwrap()
+                )
+            );
+        });
+    });
+    assert_eq!(
+        AxisSystem::PosXPosYPosZ.act(D6::R2),
+        Some(AxisSystem::PosYPosZPosX)
+    );
+    assert_eq!(AxisSystem::PosXPosYPosZ.act(D6::R1), None);
+}
+
+#[test]
+fn test_iter_all() {
+    let actions: HashSet<_> = D6::iter_all().collect();
+    assert_eq!(actions.len(), 12);
+    assert!(D6::iter_all().all(|a| D6::iter_all().all(|b| actions.contains(&(a * b)))));
+    // Even the reflections of the hexagon are realized by rotations of space.
+    assert!(D6::iter_all().all(|action| (action.into_mat3().determinant() - 1.0).abs() < 1e-6));
+    assert!(D6::iter_all().all(|action| {
+        let image = action.into_mat3() * Vec3::ONE;
+        image.abs_diff_eq(Vec3::ONE, 1e-6) == action.is_rotation()
+            && image.abs_diff_eq(Vec3::NEG_ONE, 1e-6) != action.is_rotation()
+    }));
+    assert_eq!(AxisSystem::iter_all().count(), 24);
+    assert_eq!(AxisSystem::iter_all().collect::<HashSet<_>>().len(), 24);
+    assert!(AxisSystem::iter_all()
+        .all(|axis_system| (axis_system.into_mat3().determinant() - 1.0).abs() < 1e-6));
+}
+
+#[test]
+fn test_d6_into_mat3() {
+    // Columns of each matrix in thirds, in `D6::iter_all` order.
+    #[rustfmt::skip]
+    const BASELINE_MATRICES: [[[f32; 3]; 3]; 12] = [
+        [[3.0, 0.0, 0.0], [0.0, 3.0, 0.0], [0.0, 0.0, 3.0]],
+        [[2.0, 2.0, -1.0], [-1.0, 2.0, 2.0], [2.0, -1.0, 2.0]],
+        [[0.0, 3.0, 0.0], [0.0, 0.0, 3.0], [3.0, 0.0, 0.0]],
+        [[-1.0, 2.0, 2.0], [2.0, -1.0, 2.0], [2.0, 2.0, -1.0]],
+        [[0.0, 0.0, 3.0], [3.0, 0.0, 0.0], [0.0, 3.0, 0.0]],
+        [[2.0, -1.0, 2.0], [2.0, 2.0, -1.0], [-1.0, 2.0, 2.0]],
+        [[0.0, -3.0, 0.0], [-3.0, 0.0, 0.0], [0.0, 0.0, -3.0]],
+        [[1.0, -2.0, -2.0], [-2.0, -2.0, 1.0], [-2.0, 1.0, -2.0]],
+        [[0.0, 0.0, -3.0], [0.0, -3.0, 0.0], [-3.0, 0.0, 0.0]],
+        [[-2.0, 1.0, -2.0], [1.0, -2.0, -2.0], [-2.0, -2.0, 1.0]],
+        [[-3.0, 0.0, 0.0], [0.0, 0.0, -3.0], [0.0, -3.0, 0.0]],
+        [[-2.0, -2.0, 1.0], [-2.0, 1.0, -2.0], [1.0, -2.0, -2.0]],
+    ];
+    D6::iter_all()
+        .zip(BASELINE_MATRICES)
+        .for_each(|(action, baseline_matrix)| {
+            assert!(action.into_mat3().abs_diff_eq(
+                Mat3::from_cols_array_2d(&baseline_matrix) * (1.0 / 3.0),
+                1e-6
+            ));
+        });
+    // R3 is the reflection matrix on its own.
+    assert_eq!(
+        D6::R3.into_mat3(),
+        Mat3::from_cols(
+            Vec3::new(-1.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0),
+            Vec3::new(2.0 / 3.0, -1.0 / 3.0, 2.0 / 3.0),
+            Vec3::new(2.0 / 3.0, 2.0 / 3.0, -1.0 / 3.0),
+        )
+    );
+    D6::iter_all().for_each(|a| {
+        D6::iter_all().for_each(|b| {
+            assert!((a.into_mat3() * b.into_mat3()).abs_diff_eq((a * b).into_mat3(), 1e-6));
+        });
+    });
+}
+
+#[test]
+fn test_direction_act() {
+    assert_eq!(Direction::PosX.act(D6::R2), Direction::PosY);
+    DIRECTIONS.into_iter().for_each(|direction| {
+        D6::iter_all().for_each(|a| {
+            D6::iter_all().for_each(|b| {
+                assert_eq!(direction.act(b).act(a), direction.act(a * b));
+            });
+        });
+    });
+    AXIS_SYSTEMS.into_iter().for_each(|axis_system| {
+        D6::iter_all().for_each(|action| {
+            let (x_direction, y_direction, z_direction) = axis_system.into_triplet();
+            if let Some(acted_axis_system) = axis_system.act(action) {
+                assert_eq!(
+                    acted_axis_system.into_triplet(),
+                    (
+                        x_direction.act(action),
+                        y_direction.act(action),
+                        z_direction.act(action),
+                    )
+                );
+            }
+        });
+    });
+}
+
+#[cfg(feature = "proptest")]
+proptest::proptest! {
+    #[test]
+    fn test_d6_associativity(a: D6, b: D6, c: D6) {
+        proptest::prop_assert_eq!((a * b) * c, a * (b * c));
+    }
+
+    #[test]
+    fn test_axis_system_act_law(axis_system: AxisSystem, a: D6, b: D6) {
+        proptest::prop_assume!(axis_system.act(b).is_some());
+        proptest::prop_assert_eq!(
+            axis_system.act(b).and_then(|axis_system| axis_system.act(a)),
+            axis_system.act(a * b)
+        );
+    }
+
+    #[test]
+    fn test_direction_act_law(direction: Direction, a: D6, b: D6) {
+        proptest::prop_assert_eq!(
+            a.act_on_direction(b.act_on_direction(direction)),
+            (a * b).act_on_direction(direction)
+        );
+    }
 }
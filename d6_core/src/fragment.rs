@@ -6,10 +6,12 @@ use glam::Vec3;
 use itertools::Itertools;
 
 use super::d6::AxisSystem;
+use super::grid::TileExternalAnchorPosition;
 use super::polygon::Polygon;
 use super::polygon::Polygons;
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub enum TileFragment {
     TriangleXFore,
     TriangleXRear,
@@ -33,28 +35,202 @@ pub enum TileFragment {
     ArchMinorFace,
     ArchMinorBulkSide,
     ArchMinorCompSide,
+    RampFace,
+    RampBulkSide,
+    RampCompSide,
 }
 
-fn iter_ladder_coords() -> impl Iterator<Item = Vec2> {
-    const RESOLUTION: usize = 4;
-    (RESOLUTION..=0)
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FragmentCoords {
+    Ladder,
+    Arch,
+    Ramp,
+}
+
+// The untransformed construction a fragment's polygons are generated from.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FragmentRecipe {
+    Face(FragmentCoords),
+    BulkSide(FragmentCoords),
+    CompSide(FragmentCoords),
+    Triangle,
+}
+
+impl TileFragment {
+    pub fn iter_all() -> impl Iterator<Item = Self> {
+        [
+            Self::TriangleXFore,
+            Self::TriangleXRear,
+            Self::TriangleYFore,
+            Self::TriangleYRear,
+            Self::TriangleZForeLeft,
+            Self::TriangleZForeRight,
+            Self::TriangleZSideLeft,
+            Self::TriangleZSideRight,
+            Self::TriangleZRearLeft,
+            Self::TriangleZRearRight,
+            Self::LadderMajorFace,
+            Self::LadderMajorBulkSide,
+            Self::LadderMajorCompSide,
+            Self::LadderMinorFace,
+            Self::LadderMinorBulkSide,
+            Self::LadderMinorCompSide,
+            Self::ArchMajorFace,
+            Self::ArchMajorBulkSide,
+            Self::ArchMajorCompSide,
+            Self::ArchMinorFace,
+            Self::ArchMinorBulkSide,
+            Self::ArchMinorCompSide,
+            Self::RampFace,
+            Self::RampBulkSide,
+            Self::RampCompSide,
+        ]
+        .into_iter()
+    }
+
+    pub fn recipe(self) -> FragmentRecipe {
+        match self {
+            Self::TriangleXFore
+            | Self::TriangleXRear
+            | Self::TriangleYFore
+            | Self::TriangleYRear
+            | Self::TriangleZForeLeft
+            | Self::TriangleZForeRight
+            | Self::TriangleZSideLeft
+            | Self::TriangleZSideRight
+            | Self::TriangleZRearLeft
+            | Self::TriangleZRearRight => FragmentRecipe::Triangle,
+            Self::LadderMajorFace | Self::LadderMinorFace => {
+                FragmentRecipe::Face(FragmentCoords::Ladder)
+            }
+            Self::LadderMajorBulkSide | Self::LadderMinorBulkSide => {
+                FragmentRecipe::BulkSide(FragmentCoords::Ladder)
+            }
+            Self::LadderMajorCompSide | Self::LadderMinorCompSide => {
+                FragmentRecipe::CompSide(FragmentCoords::Ladder)
+            }
+            Self::ArchMajorFace | Self::ArchMinorFace => FragmentRecipe::Face(FragmentCoords::Arch),
+            Self::ArchMajorBulkSide | Self::ArchMinorBulkSide => {
+                FragmentRecipe::BulkSide(FragmentCoords::Arch)
+            }
+            Self::ArchMajorCompSide | Self::ArchMinorCompSide => {
+                FragmentRecipe::CompSide(FragmentCoords::Arch)
+            }
+            Self::RampFace => FragmentRecipe::Face(FragmentCoords::Ramp),
+            Self::RampBulkSide => FragmentRecipe::BulkSide(FragmentCoords::Ramp),
+            Self::RampCompSide => FragmentRecipe::CompSide(FragmentCoords::Ramp),
+        }
+    }
+}
+
+impl TileFragment {
+    pub fn boundary_edges(self) -> Vec<(Vec3, Vec3)> {
+        boundary_edges(&POLYGONS_DICT[&self])
+    }
+}
+
+// Edges shared by two polygons are interior seams and get dropped. Vertices are
+// matched on a 1e-4 grid, so rounding noise and -0.0 do not split them.
+fn boundary_edges(polygons: &Polygons) -> Vec<(Vec3, Vec3)> {
+    let edge_key = |start: Vec3, end: Vec3| {
+        let (start, end) = (
+            (start * 1e4).round().as_ivec3().to_array(),
+            (end * 1e4).round().as_ivec3().to_array(),
+        );
+        (start.min(end), start.max(end))
+    };
+    let edges: Vec<_> = polygons
+        .0
+        .iter()
+        .flat_map(|polygon| {
+            polygon
+                .vertices
+                .iter()
+                .copied()
+                .circular_tuple_windows()
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    let edge_counts = edges
+        .iter()
+        .map(|&(start, end)| edge_key(start, end))
+        .counts();
+    edges
+        .into_iter()
+        .filter(|&(start, end)| edge_counts[&edge_key(start, end)] == 1)
+        .collect()
+}
+
+const LADDER_RESOLUTION: usize = 4;
+const ARCH_RESOLUTION: usize = 16;
+
+impl FragmentCoords {
+    // The resolution the fragments in `POLYGONS_DICT` are built at.
+    pub fn default_resolution(self) -> usize {
+        match self {
+            Self::Ladder => LADDER_RESOLUTION,
+            Self::Arch => ARCH_RESOLUTION,
+            Self::Ramp => 1,
+        }
+    }
+
+    // The resolution counts the steps of a ladder and the segments of an arch.
+    // A ramp is straight and ignores it. A resolution of 0 is taken as 1.
+    pub fn coords(self, resolution: usize) -> Vec<Vec2> {
+        let resolution = resolution.max(1);
+        match self {
+            Self::Ladder => iter_ladder_coords(resolution).collect(),
+            Self::Arch => iter_arch_coords(resolution).collect(),
+            Self::Ramp => iter_ramp_coords().collect(),
+        }
+    }
+}
+
+impl FragmentRecipe {
+    pub fn polygons(self, resolution: usize) -> Polygons {
+        match self {
+            Self::Face(coords) => face_polygons(coords.coords(resolution).into_iter()),
+            Self::BulkSide(coords) => bulk_side_polygons(coords.coords(resolution).into_iter()),
+            Self::CompSide(coords) => comp_side_polygons(coords.coords(resolution).into_iter()),
+            Self::Triangle => triangle_polygons(),
+        }
+    }
+}
+
+fn iter_ladder_coords(resolution: usize) -> impl Iterator<Item = Vec2> {
+    (0..=resolution)
+        .rev()
         .flat_map(|i| itertools::repeat_n(i, 2))
         .zip(
-            (0..=RESOLUTION)
+            (0..=resolution)
                 .flat_map(|i| itertools::repeat_n(i, 2))
                 .skip(1),
         )
-        .map(|(i, j)| {
-            Vec2::new(i as f32 / RESOLUTION as f32, j as f32 / RESOLUTION as f32) * 2.0 - 1.0
+        .map(move |(i, j)| {
+            Vec2::new(i as f32 / resolution as f32, j as f32 / resolution as f32) * 2.0 - 1.0
         })
 }
 
-fn iter_arch_coords() -> impl Iterator<Item = Vec2> {
-    const RESOLUTION: usize = 16;
-    (0..=RESOLUTION).map(|i| {
-        let (s, c) = (i as f32 / RESOLUTION as f32 * std::f32::consts::FRAC_PI_2).sin_cos();
-        Vec2::new(c, s) * 2.0 - 1.0
-    })
+fn iter_arch_coords(resolution: usize) -> impl Iterator<Item = Vec2> {
+    // Taking both coordinates as sines keeps the endpoints exact, since
+    // `cos(FRAC_PI_2)` does not evaluate to zero in floating point.
+    let quarter_sin =
+        move |i: usize| (i as f32 / resolution as f32 * std::f32::consts::FRAC_PI_2).sin();
+    (0..=resolution)
+        .map(move |i| Vec2::new(quarter_sin(resolution - i), quarter_sin(i)) * 2.0 - 1.0)
+}
+
+// A straight slope rising by `tan(PI / 6)` per half tile from the corner at
+// (-1, 1) across the whole tile, the same slope the ramp route climbs. A riser
+// then closes the profile at the opposite corner, as ladders do.
+fn iter_ramp_coords() -> impl Iterator<Item = Vec2> {
+    let rise = std::f32::consts::FRAC_PI_6.tan();
+    [
+        Vec2::new(1.0, -1.0),
+        Vec2::new(1.0, 1.0 - 2.0 * rise),
+        Vec2::new(-1.0, 1.0),
+    ]
+    .into_iter()
 }
 
 fn face_polygons(coords_iter: impl Iterator<Item = Vec2>) -> Polygons {
@@ -100,6 +276,40 @@ fn comp_side_polygons(coords_iter: impl Iterator<Item = Vec2>) -> Polygons {
     )
 }
 
+// The closed body between the face and the bulk corner: the face, the bulk
+// cross section capping both ends, and the two tile walls meeting at the corner.
+fn solid_polygons(coords: Vec<Vec2>) -> Polygons {
+    let wall = |start: Vec2, end: Vec2| Polygon {
+        vertices: Vec::from([
+            Vec3::from((start, -1.0)),
+            Vec3::from((start, 1.0)),
+            Vec3::from((end, 1.0)),
+            Vec3::from((end, -1.0)),
+        ]),
+        normal: Vec3::new(end.y - start.y, start.x - end.x, 0.0),
+    };
+    let bulk_corner = Vec2::new(-1.0, -1.0);
+    let walls = [
+        wall(*coords.last().unwrap(), bulk_corner),
+        wall(bulk_corner, coords[0]),
+    ];
+    let bulk_side = bulk_side_polygons(coords.iter().copied());
+    Polygons(
+        face_polygons(coords.iter().copied())
+            .0
+            .into_iter()
+            .chain(
+                bulk_side
+                    .clone()
+                    .transform(Mat4::from_scale(Vec3::new(1.0, 1.0, -1.0)))
+                    .0,
+            )
+            .chain(bulk_side.0)
+            .chain(walls)
+            .collect(),
+    )
+}
+
 fn triangle_polygons() -> Polygons {
     Polygons(
         std::iter::once(Polygon {
@@ -114,6 +324,24 @@ fn triangle_polygons() -> Polygons {
     )
 }
 
+// The triangle of the plane z = 0 lying in the corner of the tile facing the
+// given neighbor. Fore and rear triangles share their corner with their twin,
+// so the handedness decides which half of the corner square is taken.
+fn triangle_z_polygons(position: TileExternalAnchorPosition) -> Polygons {
+    let (corner, axis_system) = match position {
+        TileExternalAnchorPosition::ForeLeft => (Vec2::new(1.0, 1.0), AxisSystem::PosYNegXPosZ),
+        TileExternalAnchorPosition::ForeRight => (Vec2::new(1.0, 1.0), AxisSystem::NegYPosXPosZ),
+        TileExternalAnchorPosition::SideLeft => (Vec2::new(1.0, -1.0), AxisSystem::NegYPosXPosZ),
+        TileExternalAnchorPosition::SideRight => (Vec2::new(-1.0, 1.0), AxisSystem::PosYNegXPosZ),
+        TileExternalAnchorPosition::RearLeft => (Vec2::new(-1.0, -1.0), AxisSystem::PosYNegXPosZ),
+        TileExternalAnchorPosition::RearRight => (Vec2::new(-1.0, -1.0), AxisSystem::NegYPosXPosZ),
+    };
+    triangle_polygons().transform(
+        Mat4::from_translation(Vec3::from((corner, 0.0)))
+            * Mat4::from_mat3(axis_system.into_mat3()),
+    )
+}
+
 lazy_static::lazy_static! {
     pub static ref POLYGONS_DICT: HashMap<TileFragment, Polygons> = map_macro::hash_map! {
         TileFragment::TriangleXFore => triangle_polygons().transform(
@@ -128,59 +356,356 @@ lazy_static::lazy_static! {
         TileFragment::TriangleYRear => triangle_polygons().transform(
             Mat4::from_translation(Vec3::new(-2.0, 0.0, 0.0)) * Mat4::from_mat3(AxisSystem::NegXPosZPosY.into_mat3()),
         ),
-        TileFragment::TriangleZForeLeft => triangle_polygons().transform(
-            Mat4::from_translation(Vec3::new(1.0, 1.0, 0.0)) * Mat4::from_mat3(AxisSystem::PosYNegXPosZ.into_mat3()),
+        TileFragment::TriangleZForeLeft => triangle_z_polygons(TileExternalAnchorPosition::ForeLeft),
+        TileFragment::TriangleZForeRight => triangle_z_polygons(TileExternalAnchorPosition::ForeRight),
+        TileFragment::TriangleZSideLeft => triangle_z_polygons(TileExternalAnchorPosition::SideLeft),
+        TileFragment::TriangleZSideRight => triangle_z_polygons(TileExternalAnchorPosition::SideRight),
+        TileFragment::TriangleZRearLeft => triangle_z_polygons(TileExternalAnchorPosition::RearLeft),
+        TileFragment::TriangleZRearRight => triangle_z_polygons(TileExternalAnchorPosition::RearRight),
+        TileFragment::LadderMajorFace => face_polygons(iter_ladder_coords(LADDER_RESOLUTION)).transform(
+            Mat4::from_mat3(AxisSystem::NegXNegYPosZ.into_mat3()),
         ),
-        TileFragment::TriangleZForeRight => triangle_polygons().transform(
-            Mat4::from_translation(Vec3::new(1.0, 1.0, 0.0)) * Mat4::from_mat3(AxisSystem::NegYPosXPosZ.into_mat3()),
+        TileFragment::LadderMajorBulkSide => bulk_side_polygons(iter_ladder_coords(LADDER_RESOLUTION)).transform(
+            Mat4::from_mat3(AxisSystem::NegXNegYPosZ.into_mat3()),
         ),
-        TileFragment::TriangleZSideLeft => triangle_polygons().transform(
-            Mat4::from_translation(Vec3::new(1.0, -1.0, 0.0)) * Mat4::from_mat3(AxisSystem::NegYPosXPosZ.into_mat3()),
+        TileFragment::LadderMajorCompSide => comp_side_polygons(iter_ladder_coords(LADDER_RESOLUTION)).transform(
+            Mat4::from_mat3(AxisSystem::NegXNegYPosZ.into_mat3()),
         ),
-        TileFragment::TriangleZSideRight => triangle_polygons().transform(
-            Mat4::from_translation(Vec3::new(-1.0, 1.0, 0.0)) * Mat4::from_mat3(AxisSystem::PosYNegXPosZ.into_mat3()),
+        TileFragment::LadderMinorFace => face_polygons(iter_ladder_coords(LADDER_RESOLUTION)).transform(
+            Mat4::from_mat3(AxisSystem::PosYNegXPosZ.into_mat3()),
         ),
-        TileFragment::TriangleZRearLeft => triangle_polygons().transform(
-            Mat4::from_translation(Vec3::new(-1.0, -1.0, 0.0)) * Mat4::from_mat3(AxisSystem::PosYNegXPosZ.into_mat3()),
+        TileFragment::LadderMinorBulkSide => bulk_side_polygons(iter_ladder_coords(LADDER_RESOLUTION)).transform(
+            Mat4::from_mat3(AxisSystem::PosYNegXPosZ.into_mat3()),
         ),
-        TileFragment::TriangleZRearRight => triangle_polygons().transform(
-            Mat4::from_translation(Vec3::new(-1.0, -1.0, 0.0)) * Mat4::from_mat3(AxisSystem::NegYPosXPosZ.into_mat3()),
+        TileFragment::LadderMinorCompSide => comp_side_polygons(iter_ladder_coords(LADDER_RESOLUTION)).transform(
+            Mat4::from_mat3(AxisSystem::PosYNegXPosZ.into_mat3()),
         ),
-        TileFragment::LadderMajorFace => face_polygons(iter_ladder_coords()).transform(
+        TileFragment::ArchMajorFace => face_polygons(iter_arch_coords(ARCH_RESOLUTION)).transform(
             Mat4::from_mat3(AxisSystem::NegXNegYPosZ.into_mat3()),
         ),
-        TileFragment::LadderMajorBulkSide => bulk_side_polygons(iter_ladder_coords()).transform(
+        TileFragment::ArchMajorBulkSide => bulk_side_polygons(iter_arch_coords(ARCH_RESOLUTION)).transform(
             Mat4::from_mat3(AxisSystem::NegXNegYPosZ.into_mat3()),
         ),
-        TileFragment::LadderMajorCompSide => comp_side_polygons(iter_ladder_coords()).transform(
+        TileFragment::ArchMajorCompSide => comp_side_polygons(iter_arch_coords(ARCH_RESOLUTION)).transform(
             Mat4::from_mat3(AxisSystem::NegXNegYPosZ.into_mat3()),
         ),
-        TileFragment::LadderMinorFace => face_polygons(iter_ladder_coords()).transform(
+        TileFragment::ArchMinorFace => face_polygons(iter_arch_coords(ARCH_RESOLUTION)).transform(
             Mat4::from_mat3(AxisSystem::PosYNegXPosZ.into_mat3()),
         ),
-        TileFragment::LadderMinorBulkSide => bulk_side_polygons(iter_ladder_coords()).transform(
+        TileFragment::ArchMinorBulkSide => bulk_side_polygons(iter_arch_coords(ARCH_RESOLUTION)).transform(
             Mat4::from_mat3(AxisSystem::PosYNegXPosZ.into_mat3()),
         ),
-        TileFragment::LadderMinorCompSide => comp_side_polygons(iter_ladder_coords()).transform(
+        TileFragment::ArchMinorCompSide => comp_side_polygons(iter_arch_coords(ARCH_RESOLUTION)).transform(
             Mat4::from_mat3(AxisSystem::PosYNegXPosZ.into_mat3()),
         ),
-        TileFragment::ArchMajorFace => face_polygons(iter_arch_coords()).transform(
+        TileFragment::RampFace => face_polygons(iter_ramp_coords()).transform(
             Mat4::from_mat3(AxisSystem::NegXNegYPosZ.into_mat3()),
         ),
-        TileFragment::ArchMajorBulkSide => bulk_side_polygons(iter_arch_coords()).transform(
+        TileFragment::RampBulkSide => bulk_side_polygons(iter_ramp_coords()).transform(
             Mat4::from_mat3(AxisSystem::NegXNegYPosZ.into_mat3()),
         ),
-        TileFragment::ArchMajorCompSide => comp_side_polygons(iter_arch_coords()).transform(
+        TileFragment::RampCompSide => comp_side_polygons(iter_ramp_coords()).transform(
             Mat4::from_mat3(AxisSystem::NegXNegYPosZ.into_mat3()),
         ),
-        TileFragment::ArchMinorFace => face_polygons(iter_arch_coords()).transform(
-            Mat4::from_mat3(AxisSystem::PosYNegXPosZ.into_mat3()),
+    };
+}
+
+lazy_static::lazy_static! {
+    // The bodies enclosed by the face fragments, for collision.
+    pub static ref SOLID_POLYGONS_DICT: HashMap<TileFragment, Polygons> = map_macro::hash_map! {
+        TileFragment::LadderMajorFace => solid_polygons(iter_ladder_coords(LADDER_RESOLUTION).collect()).transform(
+            Mat4::from_mat3(AxisSystem::NegXNegYPosZ.into_mat3()),
         ),
-        TileFragment::ArchMinorBulkSide => bulk_side_polygons(iter_arch_coords()).transform(
+        TileFragment::LadderMinorFace => solid_polygons(iter_ladder_coords(LADDER_RESOLUTION).collect()).transform(
             Mat4::from_mat3(AxisSystem::PosYNegXPosZ.into_mat3()),
         ),
-        TileFragment::ArchMinorCompSide => comp_side_polygons(iter_arch_coords()).transform(
+        TileFragment::ArchMajorFace => solid_polygons(iter_arch_coords(ARCH_RESOLUTION).collect()).transform(
+            Mat4::from_mat3(AxisSystem::NegXNegYPosZ.into_mat3()),
+        ),
+        TileFragment::ArchMinorFace => solid_polygons(iter_arch_coords(ARCH_RESOLUTION).collect()).transform(
             Mat4::from_mat3(AxisSystem::PosYNegXPosZ.into_mat3()),
         ),
+        TileFragment::RampFace => solid_polygons(iter_ramp_coords().collect()).transform(
+            Mat4::from_mat3(AxisSystem::NegXNegYPosZ.into_mat3()),
+        ),
+    };
+}
+
+pub fn fragment_mesh(fragment: TileFragment) -> (Vec<Vec3>, Vec<Vec3>, Vec<u32>) {
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut index_dict: HashMap<([u32; 3], [u32; 3]), u32> = HashMap::new();
+    let indices = POLYGONS_DICT
+        .get(&fragment)
+        .unwrap()
+        .0
+        .iter()
+        .flat_map(|polygon| {
+            let normal = polygon.normal.normalize_or_zero();
+            polygon
+                .triangulate()
+                .into_iter()
+                .flatten()
+                .map(move |position| (position, normal))
+        })
+        .map(|(position, normal)| {
+            *index_dict
+                .entry((
+                    position.to_array().map(f32::to_bits),
+                    normal.to_array().map(f32::to_bits),
+                ))
+                .or_insert_with(|| {
+                    positions.push(position);
+                    normals.push(normal);
+                    positions.len() as u32 - 1
+                })
+        })
+        .collect();
+    (positions, normals, indices)
+}
+
+#[test]
+fn test_fragment_mesh() {
+    POLYGONS_DICT.keys().for_each(|&fragment| {
+        let (positions, normals, indices) = fragment_mesh(fragment);
+        assert_eq!(positions.len(), normals.len());
+        assert_eq!(indices.len() % 3, 0);
+        assert!(indices
+            .iter()
+            .all(|&index| (index as usize) < positions.len()));
+    });
+}
+
+#[test]
+fn test_ladder_polygons() {
+    // A tread and a riser for each of the four steps.
+    let face_polygons = &POLYGONS_DICT[&TileFragment::LadderMajorFace].0;
+    assert_eq!(face_polygons.len(), 8);
+    assert!(face_polygons
+        .iter()
+        .all(|polygon| polygon.vertices.len() == 4));
+    assert_eq!(
+        iter_ladder_coords(LADDER_RESOLUTION).next(),
+        Some(Vec2::new(1.0, -1.0))
+    );
+    assert_eq!(
+        iter_ladder_coords(LADDER_RESOLUTION).last(),
+        Some(Vec2::new(-1.0, 1.0))
+    );
+    assert_eq!(
+        POLYGONS_DICT[&TileFragment::LadderMinorBulkSide].0[0]
+            .vertices
+            .len(),
+        10
+    );
+}
+
+#[test]
+fn test_triangle_z_polygons() {
+    [
+        (
+            TileExternalAnchorPosition::ForeLeft,
+            Vec3::new(1.0, 1.0, 0.0),
+            AxisSystem::PosYNegXPosZ,
+        ),
+        (
+            TileExternalAnchorPosition::ForeRight,
+            Vec3::new(1.0, 1.0, 0.0),
+            AxisSystem::NegYPosXPosZ,
+        ),
+        (
+            TileExternalAnchorPosition::SideLeft,
+            Vec3::new(1.0, -1.0, 0.0),
+            AxisSystem::NegYPosXPosZ,
+        ),
+        (
+            TileExternalAnchorPosition::SideRight,
+            Vec3::new(-1.0, 1.0, 0.0),
+            AxisSystem::PosYNegXPosZ,
+        ),
+        (
+            TileExternalAnchorPosition::RearLeft,
+            Vec3::new(-1.0, -1.0, 0.0),
+            AxisSystem::PosYNegXPosZ,
+        ),
+        (
+            TileExternalAnchorPosition::RearRight,
+            Vec3::new(-1.0, -1.0, 0.0),
+            AxisSystem::NegYPosXPosZ,
+        ),
+    ]
+    .into_iter()
+    .for_each(|(position, translation, axis_system)| {
+        let expected_polygons = triangle_polygons().transform(
+            Mat4::from_translation(translation) * Mat4::from_mat3(axis_system.into_mat3()),
+        );
+        let polygons = triangle_z_polygons(position);
+        assert_eq!(polygons.0.len(), expected_polygons.0.len());
+        polygons
+            .0
+            .iter()
+            .zip(&expected_polygons.0)
+            .for_each(|(polygon, expected_polygon)| {
+                assert!(polygon.normal.abs_diff_eq(expected_polygon.normal, 1e-6));
+                assert_eq!(polygon.vertices.len(), expected_polygon.vertices.len());
+                polygon
+                    .vertices
+                    .iter()
+                    .zip(&expected_polygon.vertices)
+                    .for_each(|(vertex, expected_vertex)| {
+                        assert!(vertex.abs_diff_eq(*expected_vertex, 1e-6))
+                    });
+            });
+    });
+    // Twin triangles split a corner square between them, so the six add up to
+    // three squares.
+    let area: f32 = [
+        TileExternalAnchorPosition::ForeLeft,
+        TileExternalAnchorPosition::ForeRight,
+        TileExternalAnchorPosition::SideLeft,
+        TileExternalAnchorPosition::SideRight,
+        TileExternalAnchorPosition::RearLeft,
+        TileExternalAnchorPosition::RearRight,
+    ]
+    .into_iter()
+    .flat_map(|position| triangle_z_polygons(position).0)
+    .flat_map(|polygon| polygon.triangulate())
+    .map(|[a, b, c]| (b - a).cross(c - a).length() / 2.0)
+    .sum();
+    assert!((area - 12.0).abs() < 1e-5);
+}
+
+#[test]
+fn test_arch_coords_endpoints() {
+    let arch_coords: Vec<_> = iter_arch_coords(ARCH_RESOLUTION).collect();
+    assert_eq!(arch_coords.first(), Some(&Vec2::new(1.0, -1.0)));
+    assert_eq!(arch_coords.last(), Some(&Vec2::new(-1.0, 1.0)));
+}
+
+#[test]
+fn test_fragment_recipe() {
+    assert_eq!(
+        TileFragment::TriangleZSideLeft.recipe(),
+        FragmentRecipe::Triangle
+    );
+    assert_eq!(
+        TileFragment::LadderMinorBulkSide.recipe(),
+        FragmentRecipe::BulkSide(FragmentCoords::Ladder)
+    );
+    assert_eq!(
+        TileFragment::ArchMajorCompSide.recipe(),
+        FragmentRecipe::CompSide(FragmentCoords::Arch)
+    );
+    // The dictionary only moves the recipe geometry rigidly, so the polygon
+    // structure and the polygon areas are preserved.
+    let polygon_area = |polygon: &Polygon| -> f32 {
+        polygon
+            .triangulate()
+            .into_iter()
+            .map(|[a, b, c]| (b - a).cross(c - a).length() / 2.0)
+            .sum()
+    };
+    POLYGONS_DICT.iter().for_each(|(fragment, polygons)| {
+        let recipe = fragment.recipe();
+        let recipe_polygons = recipe.polygons(match recipe {
+            FragmentRecipe::Face(coords)
+            | FragmentRecipe::BulkSide(coords)
+            | FragmentRecipe::CompSide(coords) => coords.default_resolution(),
+            FragmentRecipe::Triangle => 1,
+        });
+        assert_eq!(polygons.0.len(), recipe_polygons.0.len());
+        polygons
+            .0
+            .iter()
+            .zip(&recipe_polygons.0)
+            .for_each(|(polygon, recipe_polygon)| {
+                assert_eq!(polygon.vertices.len(), recipe_polygon.vertices.len());
+                assert!((polygon_area(polygon) - polygon_area(recipe_polygon)).abs() < 1e-5);
+            });
+    });
+    // Regenerated at other resolutions, a ladder face has a tread and a riser
+    // per step, an arch face a quad per segment, and the caps a vertex more
+    // than the profile.
+    assert_eq!(
+        FragmentRecipe::Face(FragmentCoords::Ladder)
+            .polygons(7)
+            .0
+            .len(),
+        14
+    );
+    assert_eq!(
+        FragmentRecipe::Face(FragmentCoords::Arch)
+            .polygons(5)
+            .0
+            .len(),
+        5
+    );
+    assert_eq!(
+        FragmentRecipe::BulkSide(FragmentCoords::Arch).polygons(5).0[0]
+            .vertices
+            .len(),
+        7
+    );
+    assert_eq!(FragmentCoords::Ladder.coords(7).len(), 15);
+    assert_eq!(
+        FragmentRecipe::Face(FragmentCoords::Ramp)
+            .polygons(7)
+            .0
+            .len(),
+        2
+    );
+    // Resolution 0 falls back to 1 rather than dividing by zero.
+    [
+        FragmentCoords::Ladder,
+        FragmentCoords::Arch,
+        FragmentCoords::Ramp,
+    ]
+    .into_iter()
+    .for_each(|fragment_coords| {
+        assert_eq!(fragment_coords.coords(0), fragment_coords.coords(1));
+        assert!(FragmentRecipe::Face(fragment_coords)
+            .polygons(0)
+            .0
+            .iter()
+            .all(|polygon| polygon.vertices.iter().all(|vertex| vertex.is_finite())));
+    });
+}
+
+#[test]
+fn test_boundary_edges() {
+    let edges = TileFragment::TriangleZSideLeft.boundary_edges();
+    assert_eq!(edges.len(), 3);
+    let vertices = &POLYGONS_DICT[&TileFragment::TriangleZSideLeft].0[0].vertices;
+    assert!(edges
+        .iter()
+        .all(|(start, end)| vertices.contains(start) && vertices.contains(end)));
+    // Two unit squares sharing the edge x = 0, written once as -0.0.
+    let square = |x: f32| Polygon {
+        vertices: Vec::from([
+            Vec3::new(x, 0.0, 0.0),
+            Vec3::new(x + 1.0, 0.0, 0.0),
+            Vec3::new(x + 1.0, 1.0, 0.0),
+            Vec3::new(x, 1.0, 0.0),
+        ]),
+        normal: Vec3::Z,
     };
+    let mut left_square = square(-1.0);
+    left_square.vertices[1].x = -0.0;
+    assert_eq!(
+        boundary_edges(&Polygons(Vec::from([left_square, square(0.0)]))).len(),
+        6
+    );
+    // The ladder face is a strip of treads and risers, so only its two long
+    // sides and two ends remain.
+    let quad_count = POLYGONS_DICT[&TileFragment::LadderMajorFace].0.len();
+    assert_eq!(quad_count, 2 * LADDER_RESOLUTION);
+    let edges = TileFragment::LadderMajorFace.boundary_edges();
+    assert_eq!(edges.len(), 2 * quad_count + 2);
+    assert_eq!(
+        edges
+            .iter()
+            .filter(|(start, end)| (start.z - end.z).abs() > 1.0)
+            .count(),
+        2
+    );
 }
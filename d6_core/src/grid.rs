@@ -1,3 +1,5 @@
+use std::borrow::Borrow;
+use std::borrow::BorrowMut;
 use std::collections::HashMap;
 use std::collections::HashSet;
 
@@ -11,17 +13,23 @@ use glam::Vec3Swizzles;
 use super::d6::AxisSystem;
 use super::d6::Direction;
 use super::d6::D6;
+use super::fragment::FragmentRecipe;
 use super::fragment::TileFragment;
 use super::fragment::POLYGONS_DICT;
+use super::fragment::SOLID_POLYGONS_DICT;
+use super::metrics::Counter;
 use super::pga::Pivot;
 use super::pga::PivotalMotion;
 use super::pga::PivotalMotionTrajectory;
+use super::pga::GEOMETRY_EPSILON;
+use super::polygon::frame_polygons;
+use super::polygon::Polygon;
 use super::polygon::Polygons;
-use super::polygon::FRAME_POLYGONS;
 use super::polygon::MARKER_POLYGONS;
 use super::polygon::PLAYER_POLYGONS;
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 enum TileInternalAnchorPositionAxis {
     PlaneForeZ,
     PlaneRearZ,
@@ -31,10 +39,12 @@ enum TileInternalAnchorPositionAxis {
     LadderMinorFaceY,
     ArchMajorFaceXY,
     ArchMinorFaceXY,
+    RampFace,
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-enum TileExternalAnchorPosition {
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum TileExternalAnchorPosition {
     ForeLeft,
     ForeRight,
     SideLeft,
@@ -44,18 +54,45 @@ enum TileExternalAnchorPosition {
 }
 
 impl TileExternalAnchorPosition {
-    fn from_offset(offset: I16Vec3) -> Self {
+    // `None` unless the offset leads to one of the six neighbors.
+    pub fn try_from_offset(offset: I16Vec3) -> Option<Self> {
         match offset {
-            I16Vec3 { x: 1, y: 0, z: -1 } => Self::ForeLeft,
-            I16Vec3 { x: 0, y: 1, z: -1 } => Self::ForeRight,
-            I16Vec3 { x: 1, y: -1, z: 0 } => Self::SideLeft,
-            I16Vec3 { x: -1, y: 1, z: 0 } => Self::SideRight,
-            I16Vec3 { x: 0, y: -1, z: 1 } => Self::RearLeft,
-            I16Vec3 { x: -1, y: 0, z: 1 } => Self::RearRight,
-            _ => unreachable!(),
+            I16Vec3 { x: 1, y: 0, z: -1 } => Some(Self::ForeLeft),
+            I16Vec3 { x: 0, y: 1, z: -1 } => Some(Self::ForeRight),
+            I16Vec3 { x: 1, y: -1, z: 0 } => Some(Self::SideLeft),
+            I16Vec3 { x: -1, y: 1, z: 0 } => Some(Self::SideRight),
+            I16Vec3 { x: 0, y: -1, z: 1 } => Some(Self::RearLeft),
+            I16Vec3 { x: -1, y: 0, z: 1 } => Some(Self::RearRight),
+            _ => None,
         }
     }
 
+    fn from_offset(offset: I16Vec3) -> Self {
+        Self::try_from_offset(offset).unwrap_or_else(|| unreachable!())
+    }
+
+    fn act(self, action: D6) -> Self {
+        #[rustfmt::skip]
+        const TILE_EXTERNAL_ANCHOR_POSITION_ACTION_TABLE: [[TileExternalAnchorPosition; 6]; 12] = {
+            use TileExternalAnchorPosition as Z6;
+            [
+                [Z6::ForeLeft, Z6::ForeRight, Z6::SideLeft, Z6::SideRight, Z6::RearLeft, Z6::RearRight],
+                [Z6::ForeRight, Z6::SideRight, Z6::ForeLeft, Z6::RearRight, Z6::SideLeft, Z6::RearLeft],
+                [Z6::SideRight, Z6::RearRight, Z6::ForeRight, Z6::RearLeft, Z6::ForeLeft, Z6::SideLeft],
+                [Z6::RearRight, Z6::RearLeft, Z6::SideRight, Z6::SideLeft, Z6::ForeRight, Z6::ForeLeft],
+                [Z6::RearLeft, Z6::SideLeft, Z6::RearRight, Z6::ForeLeft, Z6::SideRight, Z6::ForeRight],
+                [Z6::SideLeft, Z6::ForeLeft, Z6::RearLeft, Z6::ForeRight, Z6::RearRight, Z6::SideRight],
+                [Z6::RearLeft, Z6::RearRight, Z6::SideLeft, Z6::SideRight, Z6::ForeLeft, Z6::ForeRight],
+                [Z6::SideLeft, Z6::RearLeft, Z6::ForeLeft, Z6::RearRight, Z6::ForeRight, Z6::SideRight],
+                [Z6::ForeLeft, Z6::SideLeft, Z6::ForeRight, Z6::RearLeft, Z6::SideRight, Z6::RearRight],
+                [Z6::ForeRight, Z6::ForeLeft, Z6::SideRight, Z6::SideLeft, Z6::RearRight, Z6::RearLeft],
+                [Z6::SideRight, Z6::ForeRight, Z6::RearRight, Z6::ForeLeft, Z6::RearLeft, Z6::SideLeft],
+                [Z6::RearRight, Z6::SideRight, Z6::RearLeft, Z6::ForeRight, Z6::SideLeft, Z6::ForeLeft],
+            ]
+        };
+        TILE_EXTERNAL_ANCHOR_POSITION_ACTION_TABLE[action as usize][self as usize]
+    }
+
     fn into_offset(self) -> I16Vec3 {
         match self {
             Self::ForeLeft => I16Vec3 { x: 1, y: 0, z: -1 },
@@ -69,6 +106,7 @@ impl TileExternalAnchorPosition {
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 enum TileExternalAnchorAxis {
     X,
     Y,
@@ -76,12 +114,25 @@ enum TileExternalAnchorAxis {
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 enum TileAnchorPositionAxis {
     Internal(TileInternalAnchorPositionAxis),
     External(TileExternalAnchorPosition, TileExternalAnchorAxis),
 }
 
+impl TileAnchorPositionAxis {
+    fn index(self) -> u8 {
+        match self {
+            Self::Internal(position_axis) => position_axis as u8,
+            Self::External(position, axis) => {
+                INTERNAL_ANCHOR_POSITION_AXES.len() as u8 + 3 * position as u8 + axis as u8
+            }
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 enum TileAnchorSign {
     Pos,
     Neg,
@@ -99,6 +150,7 @@ impl std::ops::BitXor<bool> for TileAnchorSign {
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 struct TileAnchor {
     position_axis: TileAnchorPositionAxis,
     sign: TileAnchorSign,
@@ -106,44 +158,14 @@ struct TileAnchor {
 }
 
 impl TileAnchor {
-    fn act(self, action: D6) -> Self {
-        #[rustfmt::skip]
-        const TILE_EXTERNAL_ANCHOR_POSITION_ACTION_TABLE: [[TileExternalAnchorPosition; 6]; 12] = {
-            use TileExternalAnchorPosition as Z6;
-            [
-                [Z6::ForeLeft, Z6::ForeRight, Z6::SideLeft, Z6::SideRight, Z6::RearLeft, Z6::RearRight],
-                [Z6::ForeRight, Z6::SideRight, Z6::ForeLeft, Z6::RearRight, Z6::SideLeft, Z6::RearLeft],
-                [Z6::SideRight, Z6::RearRight, Z6::ForeRight, Z6::RearLeft, Z6::ForeLeft, Z6::SideLeft],
-                [Z6::RearRight, Z6::RearLeft, Z6::SideRight, Z6::SideLeft, Z6::ForeRight, Z6::ForeLeft],
-                [Z6::RearLeft, Z6::SideLeft, Z6::RearRight, Z6::ForeLeft, Z6::SideRight, Z6::ForeRight],
-                [Z6::SideLeft, Z6::ForeLeft, Z6::RearLeft, Z6::ForeRight, Z6::RearRight, Z6::SideRight],
-                [Z6::RearLeft, Z6::RearRight, Z6::SideLeft, Z6::SideRight, Z6::ForeLeft, Z6::ForeRight],
-                [Z6::SideLeft, Z6::RearLeft, Z6::ForeLeft, Z6::RearRight, Z6::ForeRight, Z6::SideRight],
-                [Z6::ForeLeft, Z6::SideLeft, Z6::ForeRight, Z6::RearLeft, Z6::SideRight, Z6::RearRight],
-                [Z6::ForeRight, Z6::ForeLeft, Z6::SideRight, Z6::SideLeft, Z6::RearRight, Z6::RearLeft],
-                [Z6::SideRight, Z6::ForeRight, Z6::RearRight, Z6::ForeLeft, Z6::RearLeft, Z6::SideLeft],
-                [Z6::RearRight, Z6::SideRight, Z6::RearLeft, Z6::ForeRight, Z6::SideLeft, Z6::ForeLeft],
-            ]
-        };
-        #[rustfmt::skip]
-        const DIRECTION_ACTION_TABLE: [[Direction; 6]; 12] = {
-            use Direction as Z6;
-            [
-                [Z6::PosX, Z6::PosY, Z6::PosZ, Z6::NegX, Z6::NegY, Z6::NegZ],
-                [Z6::NegZ, Z6::NegX, Z6::NegY, Z6::PosZ, Z6::PosX, Z6::PosY],
-                [Z6::PosY, Z6::PosZ, Z6::PosX, Z6::NegY, Z6::NegZ, Z6::NegX],
-                [Z6::NegX, Z6::NegY, Z6::NegZ, Z6::PosX, Z6::PosY, Z6::PosZ],
-                [Z6::PosZ, Z6::PosX, Z6::PosY, Z6::NegZ, Z6::NegX, Z6::NegY],
-                [Z6::NegY, Z6::NegZ, Z6::NegX, Z6::PosY, Z6::PosZ, Z6::PosX],
-                [Z6::NegY, Z6::NegX, Z6::NegZ, Z6::PosY, Z6::PosX, Z6::PosZ],
-                [Z6::PosX, Z6::PosZ, Z6::PosY, Z6::NegX, Z6::NegZ, Z6::NegY],
-                [Z6::NegZ, Z6::NegY, Z6::NegX, Z6::PosZ, Z6::PosY, Z6::PosX],
-                [Z6::PosY, Z6::PosX, Z6::PosZ, Z6::NegY, Z6::NegX, Z6::NegZ],
-                [Z6::NegX, Z6::NegZ, Z6::NegY, Z6::PosX, Z6::PosZ, Z6::PosY],
-                [Z6::PosZ, Z6::PosY, Z6::PosX, Z6::NegZ, Z6::NegY, Z6::NegX],
-            ]
-        };
+    fn external_position(&self) -> Option<TileExternalAnchorPosition> {
+        match self.position_axis {
+            TileAnchorPositionAxis::Internal(_) => None,
+            TileAnchorPositionAxis::External(external_position, _) => Some(external_position),
+        }
+    }
 
+    fn act(self, action: D6) -> Self {
         match self {
             Self {
                 position_axis: TileAnchorPositionAxis::Internal(position_axis),
@@ -159,12 +181,9 @@ impl TileAnchor {
                 sign,
                 stationery,
             } => {
-                let new_external_position = TILE_EXTERNAL_ANCHOR_POSITION_ACTION_TABLE
-                    [action as usize][external_position as usize]
-                    as TileExternalAnchorPosition;
-                let (new_sign, new_external_axis) = (DIRECTION_ACTION_TABLE[action as usize]
-                    [Direction::from_tuple((sign, external_axis)) as usize]
-                    as Direction)
+                let new_external_position = external_position.act(action);
+                let (new_sign, new_external_axis) = Direction::from_tuple((sign, external_axis))
+                    .act(action)
                     .into_tuple();
                 Self {
                     position_axis: TileAnchorPositionAxis::External(
@@ -203,31 +222,80 @@ impl Direction {
     }
 }
 
-enum RouteMotionPrimitive {
+impl TileFragment {
+    // The fragment drawn in place of this one on a tile under `action`. The six
+    // plane triangles are permuted among themselves. No other fragment has an
+    // image among the fragments, so those stay as they are and are drawn in the
+    // rotated frame of the tile instead.
+    pub fn act(self, action: D6) -> Self {
+        let Some(external_position) = self.plane_triangle_position() else {
+            return self;
+        };
+        match external_position.act(action) {
+            TileExternalAnchorPosition::ForeLeft => Self::TriangleZForeLeft,
+            TileExternalAnchorPosition::ForeRight => Self::TriangleZForeRight,
+            TileExternalAnchorPosition::SideLeft => Self::TriangleZSideLeft,
+            TileExternalAnchorPosition::SideRight => Self::TriangleZSideRight,
+            TileExternalAnchorPosition::RearLeft => Self::TriangleZRearLeft,
+            TileExternalAnchorPosition::RearRight => Self::TriangleZRearRight,
+        }
+    }
+
+    fn plane_triangle_position(self) -> Option<TileExternalAnchorPosition> {
+        match self {
+            Self::TriangleZForeLeft => Some(TileExternalAnchorPosition::ForeLeft),
+            Self::TriangleZForeRight => Some(TileExternalAnchorPosition::ForeRight),
+            Self::TriangleZSideLeft => Some(TileExternalAnchorPosition::SideLeft),
+            Self::TriangleZSideRight => Some(TileExternalAnchorPosition::SideRight),
+            Self::TriangleZRearLeft => Some(TileExternalAnchorPosition::RearLeft),
+            Self::TriangleZRearRight => Some(TileExternalAnchorPosition::RearRight),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RouteMotionPrimitive {
     Plane,
     PlaneExt,
     Ladder,
     LadderExt,
     Arch,
     ArchExt,
+    Ramp,
+    RampRiser,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RouteKind {
+    Plane,
+    Ladder,
+    Arch,
+    Ramp,
 }
 
 impl RouteMotionPrimitive {
+    pub fn kind(&self) -> RouteKind {
+        match self {
+            &Self::Plane | &Self::PlaneExt => RouteKind::Plane,
+            &Self::Ladder | &Self::LadderExt => RouteKind::Ladder,
+            &Self::Arch | &Self::ArchExt => RouteKind::Arch,
+            &Self::Ramp | &Self::RampRiser => RouteKind::Ramp,
+        }
+    }
+
     fn pivot_motion(&self, backward: bool, flip: bool) -> PivotalMotion {
         let stem_pivot = {
             let (slope, angle) = self.slope_and_rotation_angle();
-            let angle_cot_angle = (angle != 0.0).then(|| angle / angle.tan()).unwrap_or(1.0);
+            let angle_cot_angle = Self::angle_cot_angle(angle);
             Pivot::from_plucker(
                 angle * Vec3::X,
                 (angle_cot_angle + angle * slope) * Vec3::Y
                     + (angle_cot_angle * slope - angle) * Vec3::Z,
             )
         };
-        let branch_pivot = self
-            .is_extended()
-            .then(|| Pivot::from_translation_vector(Vec3::Y));
         let motion = PivotalMotion::from_pivots(
-            branch_pivot
+            self.branch_pivot()
                 .into_iter()
                 .chain(std::iter::once(stem_pivot))
                 .collect(),
@@ -251,17 +319,38 @@ impl RouteMotionPrimitive {
         motion
     }
 
+    fn branch_pivot(&self) -> Option<Pivot> {
+        match self {
+            &Self::PlaneExt | &Self::LadderExt | &Self::ArchExt => {
+                Some(Pivot::from_translation_vector(Vec3::Y))
+            }
+            // Drops down the riser above the slope, see `iter_ramp_coords`.
+            &Self::RampRiser => Some(Pivot::from_translation_vector(
+                (2.0 * std::f32::consts::FRAC_PI_6.tan() - 2.0) * Vec3::Z,
+            )),
+            _ => None,
+        }
+    }
+
+    fn angle_cot_angle(angle: f32) -> f32 {
+        (angle.abs() > GEOMETRY_EPSILON)
+            .then(|| angle / angle.tan())
+            .unwrap_or(1.0)
+    }
+
     fn slope_and_rotation_angle(&self) -> (f32, f32) {
         match self {
             &Self::Plane | &Self::PlaneExt => (0.0, 0.0),
             &Self::Ladder | &Self::LadderExt => (1.0, 0.0),
             &Self::Arch | &Self::ArchExt => (1.0, std::f32::consts::FRAC_PI_4),
+            &Self::Ramp => (std::f32::consts::FRAC_PI_6.tan(), 0.0),
+            &Self::RampRiser => (-std::f32::consts::FRAC_PI_6.tan(), 0.0),
         }
     }
 
     fn is_extended(&self) -> bool {
         match self {
-            &Self::Plane | &Self::Ladder | &Self::Arch => false,
+            &Self::Plane | &Self::Ladder | &Self::Arch | &Self::Ramp | &Self::RampRiser => false,
             &Self::PlaneExt | &Self::LadderExt | &Self::ArchExt => true,
         }
     }
@@ -296,13 +385,14 @@ impl RouteFamilyInfo {
         let pivotal_motion = self
             .motion_primitive
             .pivot_motion(backward, flip)
-            .pivotal_global_transform(Pivot::from_rotation_matrix(self.axis_system.into_mat3()))
-            .pivotal_global_transform(Pivot::from_translation_vector(
-                self.external_position.into_offset().as_vec3(),
-            ));
+            .rotate_global(self.axis_system.into_mat3())
+            .translate_global(self.external_position.into_offset().as_vec3());
+        // Warmed once here, the distances are carried along by every clone.
+        pivotal_motion.pivot_distances();
         Route {
             initial_anchor,
             terminal_anchor,
+            motion_primitive: self.motion_primitive,
             pivotal_motion,
             fragments_requirement: self.fragments_requirement.to_vec().into_iter().collect(),
         }
@@ -312,6 +402,7 @@ impl RouteFamilyInfo {
 struct Route {
     initial_anchor: TileAnchor,
     terminal_anchor: TileAnchor,
+    motion_primitive: RouteMotionPrimitive,
     pivotal_motion: PivotalMotion,
     fragments_requirement: HashSet<TileFragment>,
 }
@@ -458,6 +549,22 @@ static ROUTE_FAMILY_INFO_LIST: &[RouteFamilyInfo] = &[
         internal_position_axis: TileInternalAnchorPositionAxis::ArchMinorFaceXY,
         fragments_requirement: &[TileFragment::TriangleYFore, TileFragment::ArchMinorFace],
     },
+    // The slope rises from the side left edge, and the side right edge sits on
+    // top of the riser above it, so both routes meet halfway up the slope.
+    RouteFamilyInfo {
+        motion_primitive: RouteMotionPrimitive::Ramp,
+        axis_system: AxisSystem::NegZNegXPosY,
+        external_position: TileExternalAnchorPosition::SideLeft,
+        internal_position_axis: TileInternalAnchorPositionAxis::RampFace,
+        fragments_requirement: &[TileFragment::RampFace],
+    },
+    RouteFamilyInfo {
+        motion_primitive: RouteMotionPrimitive::RampRiser,
+        axis_system: AxisSystem::PosZPosXPosY,
+        external_position: TileExternalAnchorPosition::SideRight,
+        internal_position_axis: TileInternalAnchorPositionAxis::RampFace,
+        fragments_requirement: &[TileFragment::RampFace],
+    },
 ];
 
 lazy_static::lazy_static! {
@@ -472,15 +579,77 @@ lazy_static::lazy_static! {
             ]
         })
         .collect();
+    // Routes grouped by their initial anchor, keeping the order of `ROUTE_LIST`.
+    static ref ROUTE_INDEX: HashMap<TileAnchor, Vec<&'static Route>> =
+        ROUTE_LIST.iter().fold(HashMap::new(), |mut route_index, route| {
+            route_index
+                .entry(route.initial_anchor)
+                .or_insert_with(Vec::new)
+                .push(route);
+            route_index
+        });
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 struct Tile {
     fragments: HashSet<TileFragment>,
+    // Maps the tile's own frame into the grid. Tile edits compose on the
+    // right, so they always act in the tile's own frame.
     action: D6,
 }
 
+// Fragments may share a tile only if some group lists them all. The plane
+// triangles cut through the middle of the tile, where the faces of the solid
+// bodies run, and the caps along the X and Y axes only make sense next to the
+// minor bodies whose extended routes rely on them.
+#[rustfmt::skip]
+static COMPATIBLE_FRAGMENT_GROUPS: &[&[TileFragment]] = &[
+    &[
+        TileFragment::TriangleZForeLeft, TileFragment::TriangleZForeRight,
+        TileFragment::TriangleZSideLeft, TileFragment::TriangleZSideRight,
+        TileFragment::TriangleZRearLeft, TileFragment::TriangleZRearRight,
+    ],
+    &[TileFragment::LadderMajorFace, TileFragment::LadderMajorBulkSide, TileFragment::LadderMajorCompSide],
+    &[
+        TileFragment::LadderMinorFace, TileFragment::LadderMinorBulkSide, TileFragment::LadderMinorCompSide,
+        TileFragment::TriangleXFore, TileFragment::TriangleXRear,
+        TileFragment::TriangleYFore, TileFragment::TriangleYRear,
+    ],
+    &[TileFragment::ArchMajorFace, TileFragment::ArchMajorBulkSide, TileFragment::ArchMajorCompSide],
+    &[
+        TileFragment::ArchMinorFace, TileFragment::ArchMinorBulkSide, TileFragment::ArchMinorCompSide,
+        TileFragment::TriangleXFore, TileFragment::TriangleXRear,
+        TileFragment::TriangleYFore, TileFragment::TriangleYRear,
+    ],
+    &[TileFragment::RampFace, TileFragment::RampBulkSide, TileFragment::RampCompSide],
+];
+
+impl Tile {
+    // Pairs in the order of `TileFragment::iter_all`.
+    fn conflicting_fragment_pairs(&self) -> Vec<(TileFragment, TileFragment)> {
+        let fragments: Vec<_> = TileFragment::iter_all()
+            .filter(|fragment| self.fragments.contains(fragment))
+            .collect();
+        fragments
+            .iter()
+            .enumerate()
+            .flat_map(|(index, &fragment_0)| {
+                fragments[index + 1..]
+                    .iter()
+                    .map(move |&fragment_1| (fragment_0, fragment_1))
+            })
+            .filter(|(fragment_0, fragment_1)| {
+                !COMPATIBLE_FRAGMENT_GROUPS
+                    .iter()
+                    .any(|group| group.contains(fragment_0) && group.contains(fragment_1))
+            })
+            .collect()
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct GridCoord(I16Vec3);
 
 impl GridCoord {
@@ -492,270 +661,2641 @@ impl GridCoord {
         2.0 * self.0.as_vec3()
     }
 
+    // Positions differing by a multiple of (1, 1, 1) look the same on screen, so
+    // that component is dropped and the rest is rounded to the nearest coordinate
+    // whose components sum to zero.
+    fn from_position(position: Vec3) -> Self {
+        let position = (position - Vec3::splat(position.element_sum() / 3.0)) / 2.0;
+        let mut coord = position.round();
+        let error = (coord - position).abs();
+        let excess = coord.element_sum();
+        if error.x >= error.y && error.x >= error.z {
+            coord.x -= excess;
+        } else if error.y >= error.z {
+            coord.y -= excess;
+        } else {
+            coord.z -= excess;
+        }
+        Self(coord.as_i16vec3())
+    }
+
     fn add_offset(self, offset: I16Vec3) -> Self {
         Self(self.0 + offset)
     }
+
+    // The six coordinates sharing an edge with this one, whether occupied or
+    // not, in the order of `TileExternalAnchorPosition`.
+    pub fn iter_neighbor_coords(self) -> impl Iterator<Item = Self> {
+        EXTERNAL_ANCHOR_POSITIONS
+            .into_iter()
+            .map(move |position| self.add_offset(position.into_offset()))
+    }
+
+    // Agrees with `TileExternalAnchorPosition::act` on the neighbor offsets.
+    fn act(self, action: D6) -> Self {
+        Self(
+            [Direction::PosX, Direction::PosY, Direction::PosZ]
+                .into_iter()
+                .zip(self.0.to_array())
+                .map(|(direction, component)| {
+                    direction.act(action).into_vec3().as_i16vec3() * component
+                })
+                .sum(),
+        )
+    }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct MovementState {
     grid_coord: GridCoord,
     anchor: TileAnchor,
 }
 
-#[derive(Clone, Debug)]
-pub struct MovementTarget {
-    movement_state: MovementState,
-    transform: Mat4,
-    pivotal_motions: Vec<PivotalMotion>,
-}
+impl MovementState {
+    fn act(self, action: D6) -> Self {
+        Self {
+            grid_coord: self.grid_coord.act(action),
+            anchor: self.anchor.act(action),
+        }
+    }
 
-#[derive(Clone)]
-pub struct Grid {
-    tile_dict: HashMap<GridCoord, Tile>,
-    movement_state: MovementState,
-    player_transform: Mat4,
-}
+    pub fn grid_coord(&self) -> GridCoord {
+        self.grid_coord
+    }
 
-impl Grid {
-    fn rotation_matrix_from_action(action: D6) -> Mat3 {
-        const REFLECTION_MATRIX: Mat3 = Mat3::from_cols_array_2d(&[
-            [-1.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0],
-            [2.0 / 3.0, -1.0 / 3.0, 2.0 / 3.0],
-            [2.0 / 3.0, 2.0 / 3.0, -1.0 / 3.0],
-        ]);
-        let (axis_system, reflect) = match action {
-            D6::R0 => (AxisSystem::PosXPosYPosZ, false),
-            D6::R1 => (AxisSystem::PosZPosXPosY, true),
-            D6::R2 => (AxisSystem::PosYPosZPosX, false),
-            D6::R3 => (AxisSystem::PosXPosYPosZ, true),
-            D6::R4 => (AxisSystem::PosZPosXPosY, false),
-            D6::R5 => (AxisSystem::PosYPosZPosX, true),
-            D6::S0 => (AxisSystem::NegYNegXNegZ, false),
-            D6::S1 => (AxisSystem::NegXNegZNegY, true),
-            D6::S2 => (AxisSystem::NegZNegYNegX, false),
-            D6::S3 => (AxisSystem::NegYNegXNegZ, true),
-            D6::S4 => (AxisSystem::NegXNegZNegY, false),
-            D6::S5 => (AxisSystem::NegZNegYNegX, true),
-        };
-        reflect
-            .then_some(REFLECTION_MATRIX)
-            .unwrap_or(Mat3::IDENTITY)
-            * axis_system.into_mat3()
+    // A stable, hashable identity of the state: position and axis, sign, and
+    // stationery.
+    pub fn anchor_key(&self) -> (GridCoord, [u8; 3]) {
+        (
+            self.grid_coord,
+            [
+                self.anchor.position_axis.index(),
+                self.anchor.sign as u8,
+                self.anchor.stationery as u8,
+            ],
+        )
+    }
+
+    // Picks one of a state and its synonym so that both compare equal.
+    fn canonical(self) -> Self {
+        std::iter::once(self)
+            .chain(self.synonym())
+            .min_by_key(|movement_state| movement_state.grid_coord.0.to_array())
+            .unwrap()
     }
 
-    fn movement_state_synonym(movement_state: MovementState) -> Option<MovementState> {
-        match movement_state.anchor.position_axis {
+    pub fn synonym(self) -> Option<Self> {
+        match self.anchor.position_axis {
             TileAnchorPositionAxis::Internal(_) => None,
             TileAnchorPositionAxis::External(external_position, external_axis) => {
                 let coord_offset = external_position.into_offset();
                 let external_position = TileExternalAnchorPosition::from_offset(-coord_offset);
-                Some(MovementState {
-                    grid_coord: movement_state.grid_coord.add_offset(coord_offset),
+                Some(Self {
+                    grid_coord: self.grid_coord.add_offset(coord_offset),
                     anchor: TileAnchor {
                         position_axis: TileAnchorPositionAxis::External(
                             external_position,
                             external_axis,
                         ),
-                        sign: movement_state.anchor.sign,
-                        stationery: movement_state.anchor.stationery,
+                        sign: self.anchor.sign,
+                        stationery: self.anchor.stationery,
                     },
                 })
             }
         }
     }
+}
 
-    fn iter_next_movement_targets_from(
-        movement_state: MovementState,
-        tile_dict: &HashMap<GridCoord, Tile>,
-    ) -> Box<dyn Iterator<Item = MovementTarget> + '_> {
-        Box::new(
-            std::iter::once(movement_state)
-                .chain(Grid::movement_state_synonym(movement_state))
-                .flat_map(move |initial_movement_state| {
-                    tile_dict
-                        .get(&initial_movement_state.grid_coord)
-                        .into_iter()
-                        .flat_map(move |tile| {
-                            ROUTE_LIST.iter().filter_map(move |route| {
-                                route
-                                    .fragments_requirement
-                                    .is_subset(&tile.fragments)
-                                    .then_some(())?;
-                                let action = tile.action;
-                                (route.initial_anchor.act(action) == initial_movement_state.anchor)
-                                    .then_some(())?;
-                                Some((
-                                    MovementState {
-                                        grid_coord: initial_movement_state.grid_coord,
-                                        anchor: route.terminal_anchor.act(action),
-                                    },
-                                    route
-                                        .pivotal_motion
-                                        .clone()
-                                        .pivotal_global_transform(Pivot::from_rotation_matrix(
-                                            Self::rotation_matrix_from_action(action),
-                                        ))
-                                        .pivotal_global_transform(Pivot::from_translation_vector(
-                                            initial_movement_state.grid_coord.grid_position(),
-                                        )),
-                                ))
-                            })
-                        })
-                })
-                .flat_map(|(terminal_movement_state, pivotal_motion)| {
-                    terminal_movement_state
-                        .anchor
-                        .stationery
-                        .then(|| {
-                            Box::new(std::iter::once(MovementTarget {
-                                movement_state: terminal_movement_state,
-                                transform: pivotal_motion.target(),
-                                pivotal_motions: Vec::new(),
-                            }))
-                                as Box<dyn Iterator<Item = MovementTarget>>
-                        })
-                        .unwrap_or_else(|| {
-                            Self::iter_next_movement_targets_from(
-                                terminal_movement_state,
-                                tile_dict,
-                            )
-                        })
-                        .into_iter()
-                        .map(move |successive_movement_target| MovementTarget {
-                            pivotal_motions: std::iter::once(pivotal_motion.clone())
-                                .chain(successive_movement_target.pivotal_motions)
-                                .collect(),
-                            ..successive_movement_target
-                        })
-                })
-                .filter(move |movement_target| {
-                    std::iter::once(movement_state)
-                        .chain(Grid::movement_state_synonym(movement_state))
-                        .all(|initial_movement_state| {
-                            initial_movement_state != movement_target.movement_state
-                        })
-                }),
-        )
-    }
-
-    fn iter_next_movement_targets(&self) -> Box<dyn Iterator<Item = MovementTarget> + '_> {
-        Self::iter_next_movement_targets_from(self.movement_state, &self.tile_dict)
-    }
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Replay(Vec<MovementState>);
 
-    pub fn iter_coords(&self) -> impl Iterator<Item = GridCoord> + '_ {
-        self.tile_dict.keys().cloned()
-    }
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EditError {
+    MissingTile(GridCoord),
+    OccupiedTile(GridCoord),
+    ConflictingFragments(GridCoord),
+}
 
-    fn conformal_transform(vector: Vec3) -> Vec2 {
-        // The rotation transforms:
-        // normalize((-1,  1,  0)) |-> (1, 0, 0)
-        // normalize((-1, -1,  2)) |-> (0, 1, 0)
-        // normalize(( 1,  1,  1)) |-> (0, 0, 1)
-        // This is a unitary matrix, so the inverse is its transpose.
-        lazy_static::lazy_static! {
-            static ref CONFORMAL_PROJECTION_MATRIX: Mat3 = Mat3::from_cols(
-                Vec3::new(-1.0, 1.0, 0.0).normalize(),
-                Vec3::new(-1.0, -1.0, 2.0).normalize(),
-                Vec3::new(1.0, 1.0, 1.0).normalize(),
-            ).transpose();
+impl std::fmt::Display for EditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingTile(GridCoord(coord)) => write!(f, "no tile at {coord}"),
+            Self::OccupiedTile(GridCoord(coord)) => {
+                write!(f, "the tile at {coord} is occupied by the player")
+            }
+            Self::ConflictingFragments(GridCoord(coord)) => {
+                write!(f, "the fragments of the tile at {coord} would conflict")
+            }
         }
-        CONFORMAL_PROJECTION_MATRIX.mul_vec3(vector).xy()
     }
+}
 
-    fn iter_shapes_from_polygons(polygons: Polygons) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> {
-        polygons.0.into_iter().map(|polygon| {
-            (
-                polygon
-                    .vertices
-                    .into_iter()
-                    .map(|vertex| Self::conformal_transform(vertex))
-                    .collect(),
-                polygon.normal,
-            )
-        })
-    }
+impl std::error::Error for EditError {}
 
-    pub fn iter_tile_fragment_shapes(
-        &self,
-        coord: GridCoord,
-    ) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + '_ {
-        self.tile_dict
-            .get(&coord)
-            .into_iter()
-            .flat_map(move |tile| &tile.fragments)
-            .flat_map(move |tile_fragment| {
-                Self::iter_shapes_from_polygons(
-                    POLYGONS_DICT
-                        .get(tile_fragment)
-                        .unwrap()
-                        .clone()
-                        .transform(Mat4::from_translation(coord.grid_position())),
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ViewError {
+    UpsideDown(D6),
+}
+
+impl std::fmt::Display for ViewError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UpsideDown(action) => {
+                write!(
+                    f,
+                    "the view action {action:?} would look at the scene from below"
                 )
-            })
+            }
+        }
     }
+}
 
-    pub fn iter_tile_frame_shapes(
-        &self,
-        coord: GridCoord,
-    ) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + '_ {
-        self.tile_dict.get(&coord).into_iter().flat_map(move |_| {
-            Self::iter_shapes_from_polygons(
-                FRAME_POLYGONS
-                    .clone()
-                    .transform(Mat4::from_translation(coord.grid_position())),
-            )
-        })
-    }
+impl std::error::Error for ViewError {}
 
-    pub fn iter_player_shapes(&self) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + '_ {
-        Self::iter_shapes_from_polygons(PLAYER_POLYGONS.clone().transform(self.player_transform))
-    }
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct TileData {
+    pub coord: GridCoord,
+    pub fragments: Vec<TileFragment>,
+    pub action: D6,
+}
 
-    pub fn iter_marker_shapes(&self) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + '_ {
-        self.iter_next_movement_targets()
-            .flat_map(|movement_target| {
-                Self::iter_shapes_from_polygons(
-                    MARKER_POLYGONS.clone().transform(movement_target.transform),
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct LevelData {
+    pub tiles: Vec<TileData>,
+    pub movement_state: MovementState,
+    pub player_transform: Mat4,
+}
+
+// The live progress on a level, as opposed to the level definition itself.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct GridSnapshot {
+    pub movement_state: MovementState,
+    pub player_transform: [f32; 16],
+    // Ends with `movement_state` unless no move has been made.
+    pub move_history: Vec<MovementState>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RestoreError {
+    MissingTile(GridCoord),
+    HistoryMismatch,
+}
+
+impl std::fmt::Display for RestoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingTile(GridCoord(coord)) => {
+                write!(
+                    f,
+                    "the snapshot places the player at {coord}, where no tile is defined"
                 )
-            })
+            }
+            Self::HistoryMismatch => {
+                write!(f, "the snapshot's move history does not end at the player")
+            }
+        }
     }
+}
 
-    pub fn motion_trajectory(&mut self, cursor_coord: Vec2) -> Option<PivotalMotionTrajectory> {
-        const RADIUS_THRESHOLD: f32 = 1.0;
-        const ANGLE_THRESHOLD: f32 = std::f32::consts::FRAC_PI_6;
-        self.iter_next_movement_targets()
-            .filter_map(|movement_target| {
-                let player_coord =
-                    Self::conformal_transform(self.player_transform.transform_point3(Vec3::ZERO));
-                ((cursor_coord - player_coord).length() > RADIUS_THRESHOLD).then_some(())?;
-                let target_coord = Self::conformal_transform(
-                    movement_target.transform.transform_point3(Vec3::ZERO),
-                );
-                let abs_angle = (target_coord - player_coord)
-                    .angle_to(cursor_coord - player_coord)
-                    .abs();
-                (abs_angle < ANGLE_THRESHOLD).then_some(())?;
-                Some((movement_target, abs_angle))
-            })
-            .min_by(|(_, abs_angle_0), (_, abs_angle_1)| abs_angle_0.total_cmp(abs_angle_1))
-            .map(|(movement_target, _)| {
-                self.movement_state = movement_target.movement_state;
-                PivotalMotionTrajectory::from_pivotal_motions(movement_target.pivotal_motions)
-            })
+impl std::error::Error for RestoreError {}
+
+// The compact level encoding is the magic and version byte followed by the
+// bincode-serialized `LevelData`.
+#[cfg(feature = "compact")]
+const LEVEL_BYTES_MAGIC: [u8; 4] = *b"D6LV";
+#[cfg(feature = "compact")]
+const LEVEL_BYTES_VERSION: u8 = 3;
+const INTERNAL_ANCHOR_POSITION_AXES: [TileInternalAnchorPositionAxis; 9] = [
+    TileInternalAnchorPositionAxis::PlaneForeZ,
+    TileInternalAnchorPositionAxis::PlaneRearZ,
+    TileInternalAnchorPositionAxis::LadderMajorFaceX,
+    TileInternalAnchorPositionAxis::LadderMajorFaceY,
+    TileInternalAnchorPositionAxis::LadderMinorFaceX,
+    TileInternalAnchorPositionAxis::LadderMinorFaceY,
+    TileInternalAnchorPositionAxis::ArchMajorFaceXY,
+    TileInternalAnchorPositionAxis::ArchMinorFaceXY,
+    TileInternalAnchorPositionAxis::RampFace,
+];
+const EXTERNAL_ANCHOR_POSITIONS: [TileExternalAnchorPosition; 6] = [
+    TileExternalAnchorPosition::ForeLeft,
+    TileExternalAnchorPosition::ForeRight,
+    TileExternalAnchorPosition::SideLeft,
+    TileExternalAnchorPosition::SideRight,
+    TileExternalAnchorPosition::RearLeft,
+    TileExternalAnchorPosition::RearRight,
+];
+
+#[cfg(feature = "compact")]
+impl LevelData {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::from(LEVEL_BYTES_MAGIC);
+        bytes.push(LEVEL_BYTES_VERSION);
+        bincode::serialize_into(&mut bytes, self).unwrap();
+        bytes
     }
 
-    pub fn set_player_transform(&mut self, player_transform: Mat4) {
-        self.player_transform = player_transform;
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, LoadError> {
+        let (magic, bytes) = bytes.split_first_chunk::<4>().ok_or(LoadError::Truncated)?;
+        (*magic == LEVEL_BYTES_MAGIC)
+            .then_some(())
+            .ok_or(LoadError::InvalidHeader)?;
+        let (&version, bytes) = bytes.split_first().ok_or(LoadError::Truncated)?;
+        (version == LEVEL_BYTES_VERSION)
+            .then_some(())
+            .ok_or(LoadError::UnsupportedVersion(version))?;
+        bincode::deserialize(bytes).map_err(LoadError::Bincode)
     }
 }
 
-lazy_static::lazy_static! {
-    pub static ref WORLD_LIST: Vec<Grid> = vec![
-        Grid {
-            tile_dict: map_macro::hash_map! {
-                GridCoord::new(0, 0, 0) => Tile {
-                    fragments: map_macro::hash_set! {
+#[derive(Clone, Debug, PartialEq)]
+pub enum LevelError {
+    DuplicateTile(GridCoord),
+    DuplicateFragment(GridCoord, TileFragment),
+    MissingStartTile(GridCoord),
+}
+
+impl std::fmt::Display for LevelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DuplicateTile(GridCoord(coord)) => {
+                write!(f, "more than one tile defined at {coord}")
+            }
+            Self::DuplicateFragment(GridCoord(coord), fragment) => {
+                write!(f, "fragment {fragment:?} listed more than once at {coord}")
+            }
+            Self::MissingStartTile(GridCoord(coord)) => {
+                write!(f, "the player starts at {coord}, where no tile is defined")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationError {
+    IncompatibleFragments(GridCoord, TileFragment, TileFragment),
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IncompatibleFragments(GridCoord(coord), fragment_0, fragment_1) => {
+                write!(
+                    f,
+                    "fragments {fragment_0:?} and {fragment_1:?} cannot share the tile at {coord}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+#[derive(Debug)]
+pub enum LoadError {
+    #[cfg(feature = "serde")]
+    Json(serde_json::Error),
+    #[cfg(feature = "compact")]
+    Bincode(bincode::Error),
+    #[cfg(feature = "compact")]
+    InvalidHeader,
+    #[cfg(feature = "compact")]
+    UnsupportedVersion(u8),
+    #[cfg(feature = "compact")]
+    Truncated,
+    Level(LevelError),
+}
+
+impl From<LevelError> for LoadError {
+    fn from(error: LevelError) -> Self {
+        Self::Level(error)
+    }
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            #[cfg(feature = "serde")]
+            Self::Json(error) => write!(f, "malformed level json: {error}"),
+            #[cfg(feature = "compact")]
+            Self::Bincode(error) => write!(f, "malformed level data: {error}"),
+            #[cfg(feature = "compact")]
+            Self::InvalidHeader => write!(f, "not a level file"),
+            #[cfg(feature = "compact")]
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported level format version {version}, expected {LEVEL_BYTES_VERSION}"
+            ),
+            #[cfg(feature = "compact")]
+            Self::Truncated => write!(f, "the level data ends unexpectedly"),
+            Self::Level(error) => write!(f, "invalid level: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "serde")]
+            Self::Json(error) => Some(error),
+            #[cfg(feature = "compact")]
+            Self::Bincode(error) => Some(error),
+            #[cfg(feature = "compact")]
+            Self::InvalidHeader | Self::UnsupportedVersion(_) | Self::Truncated => None,
+            Self::Level(error) => Some(error),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StartAnchor {
+    PlaneForeZ,
+    PlaneRearZ,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BuildError {
+    EmptyLevel,
+    MissingStart,
+    MissingStartTile(GridCoord),
+    // No route ends at the start anchor, so the player pose is unknown.
+    UnsupportedStart(GridCoord),
+}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptyLevel => write!(f, "the level has no tiles"),
+            Self::MissingStart => write!(f, "no start position was given"),
+            Self::MissingStartTile(GridCoord(coord)) => {
+                write!(f, "the player starts at {coord}, where no tile is defined")
+            }
+            Self::UnsupportedStart(GridCoord(coord)) => {
+                write!(f, "the tile at {coord} offers no place to start on")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+#[derive(Clone, Debug, Default)]
+pub struct GridBuilder {
+    tile_dict: HashMap<GridCoord, Tile>,
+    start: Option<(GridCoord, StartAnchor)>,
+}
+
+impl GridBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Inserting at an occupied coordinate replaces the previous tile.
+    pub fn insert_tile(
+        mut self,
+        coord: GridCoord,
+        fragments: HashSet<TileFragment>,
+        action: D6,
+    ) -> Self {
+        self.tile_dict.insert(coord, Tile { fragments, action });
+        self
+    }
+
+    pub fn start(mut self, coord: GridCoord, anchor: StartAnchor) -> Self {
+        self.start = Some((coord, anchor));
+        self
+    }
+
+    pub fn build(self) -> Result<Grid, BuildError> {
+        (!self.tile_dict.is_empty())
+            .then_some(())
+            .ok_or(BuildError::EmptyLevel)?;
+        let (start_coord, start_anchor) = self.start.ok_or(BuildError::MissingStart)?;
+        self.tile_dict
+            .contains_key(&start_coord)
+            .then_some(())
+            .ok_or(BuildError::MissingStartTile(start_coord))?;
+        let movement_state = MovementState {
+            grid_coord: start_coord,
+            anchor: TileAnchor {
+                position_axis: TileAnchorPositionAxis::Internal(match start_anchor {
+                    StartAnchor::PlaneForeZ => TileInternalAnchorPositionAxis::PlaneForeZ,
+                    StartAnchor::PlaneRearZ => TileInternalAnchorPositionAxis::PlaneRearZ,
+                }),
+                sign: TileAnchorSign::Pos,
+                stationery: true,
+            },
+        };
+        let mut grid = Grid::from_parts(self.tile_dict, movement_state, Mat4::IDENTITY);
+        grid.player_transform = grid
+            .movement_state_transform(movement_state)
+            .ok_or(BuildError::UnsupportedStart(start_coord))?;
+        Ok(grid)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MovementTarget {
+    movement_state: MovementState,
+    transform: Mat4,
+    pivotal_motions: Vec<PivotalMotion>,
+    motion_primitives: Vec<RouteMotionPrimitive>,
+}
+
+impl MovementTarget {
+    pub fn movement_state(&self) -> MovementState {
+        self.movement_state
+    }
+
+    pub fn motion_primitives(&self) -> &[RouteMotionPrimitive] {
+        &self.motion_primitives
+    }
+
+    // Chained moves walk across planes to reach a ladder or an arch, so the
+    // first non-plane route decides the kind.
+    pub fn route_kind(&self) -> RouteKind {
+        self.motion_primitives
+            .iter()
+            .map(RouteMotionPrimitive::kind)
+            .find(|&route_kind| route_kind != RouteKind::Plane)
+            .unwrap_or(RouteKind::Plane)
+    }
+}
+
+// Movement targets of the primary player, valid while its movement state
+// matches. A mutex rather than a `RefCell` keeps `Grid` shareable.
+#[derive(Debug, Default)]
+struct TargetCache {
+    entry: std::sync::Mutex<Option<(MovementState, std::sync::Arc<[MovementTarget]>)>>,
+    computation_counter: Counter,
+}
+
+impl TargetCache {
+    fn get_or_compute(
+        &self,
+        movement_state: MovementState,
+        compute: impl FnOnce() -> Vec<MovementTarget>,
+    ) -> std::sync::Arc<[MovementTarget]> {
+        let mut cache = self.entry.lock().unwrap();
+        match cache.as_ref() {
+            Some((cached_movement_state, movement_targets))
+                if *cached_movement_state == movement_state =>
+            {
+                movement_targets.clone()
+            }
+            _ => {
+                self.computation_counter.increment();
+                let movement_targets: std::sync::Arc<[MovementTarget]> = compute().into();
+                *cache = Some((movement_state, movement_targets.clone()));
+                movement_targets
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        *self.entry.get_mut().unwrap() = None;
+    }
+}
+
+// Clones recompute their targets lazily.
+impl Clone for TargetCache {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+// A group of polygons in an OBJ export, opening a new object when `object` is
+// set.
+struct ObjGroup {
+    object: Option<String>,
+    group: String,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct PlayerId(usize);
+
+impl PlayerId {
+    // The player driven by `motion_trajectory` and friends.
+    pub const PRIMARY: Self = Self(0);
+}
+
+#[derive(Clone, Debug)]
+struct Player {
+    movement_state: MovementState,
+    transform: Mat4,
+}
+
+#[derive(Clone, Debug)]
+struct MoveRecord {
+    previous_movement_state: MovementState,
+    movement_target: MovementTarget,
+}
+
+#[derive(Clone, Debug)]
+struct Checkpoint {
+    movement_state: MovementState,
+    player_transform: Mat4,
+    move_history: Vec<MovementState>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameStyle {
+    pub thickness: f32,
+    pub color: [f32; 4],
+}
+
+impl Default for FrameStyle {
+    fn default() -> Self {
+        Self {
+            thickness: 0.05,
+            color: [0.0, 0.0, 0.0, 1.0],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ShapeKind {
+    Fragment,
+    Frame,
+    Player,
+    Marker,
+}
+
+// How far from the player, in projected units, and how close in angle to a
+// target the cursor must be to pick that target.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MotionConfig {
+    pub radius_threshold: f32,
+    pub angle_threshold: f32,
+}
+
+impl Default for MotionConfig {
+    fn default() -> Self {
+        Self {
+            radius_threshold: 1.0,
+            angle_threshold: std::f32::consts::FRAC_PI_6,
+        }
+    }
+}
+
+// Points from the scene towards the viewer, along the projection axis.
+const VIEW_DIRECTION: Vec3 = Vec3::ONE;
+
+lazy_static::lazy_static! {
+    // The rotation transforms:
+    // normalize((-1,  1,  0)) |-> (1, 0, 0)
+    // normalize((-1, -1,  2)) |-> (0, 1, 0)
+    // normalize(( 1,  1,  1)) |-> (0, 0, 1)
+    // This is a unitary matrix, so the inverse is its transpose.
+    static ref CONFORMAL_PROJECTION_MATRIX: Mat3 = Mat3::from_cols(
+        Vec3::new(-1.0, 1.0, 0.0).normalize(),
+        Vec3::new(-1.0, -1.0, 2.0).normalize(),
+        Vec3::new(1.0, 1.0, 1.0).normalize(),
+    ).transpose();
+}
+
+#[derive(Clone)]
+pub struct Grid {
+    tile_dict: HashMap<GridCoord, Tile>,
+    movement_state: MovementState,
+    player_transform: Mat4,
+    // Counts the routes examined by the latest movement target query.
+    route_evaluation_counter: Counter,
+    // Where the current recording starts in `move_history`.
+    recording_start: Option<usize>,
+    frame_style: FrameStyle,
+    motion_config: MotionConfig,
+    // Players other than the primary one, indexed by `PlayerId(index + 1)`.
+    co_players: Vec<Player>,
+    player_collision: bool,
+    // Inclusive lower and upper corners of the region players may move in.
+    play_bounds: Option<(GridCoord, GridCoord)>,
+    goal: Option<MovementState>,
+    // Transient world-space transforms of whole tiles, e.g. for tiles sliding
+    // in. They only affect the drawn shapes.
+    tile_transform_dict: HashMap<GridCoord, Mat4>,
+    checkpoint: Option<Checkpoint>,
+    // States reached by the primary player's moves since the level started.
+    // The move count and recordings are read off it.
+    move_history: Vec<MovementState>,
+    undo_stack: Vec<MoveRecord>,
+    redo_stack: Vec<MoveRecord>,
+    // The most moves that can be undone, unbounded if absent.
+    history_limit: Option<usize>,
+    // Keyed by the movement state; edits to the level or to other players
+    // clear it.
+    target_cache: TargetCache,
+    // Applied on top of the fixed view direction before projecting.
+    view_action: D6,
+}
+
+impl Grid {
+    fn rotation_matrix_from_action(action: D6) -> Mat3 {
+        action.into_mat3()
+    }
+
+    // The routes whose initial anchor, acted on by `action`, is `anchor`.
+    fn iter_candidate_routes(
+        anchor: TileAnchor,
+        action: D6,
+    ) -> impl Iterator<Item = &'static Route> {
+        ROUTE_INDEX
+            .get(&anchor.act(action.inverse()))
+            .into_iter()
+            .flatten()
+            .copied()
+    }
+
+    fn iter_next_movement_targets_from<'a>(
+        movement_state: MovementState,
+        tile_dict: &'a HashMap<GridCoord, Tile>,
+        route_evaluation_counter: &'a Counter,
+    ) -> Box<dyn Iterator<Item = MovementTarget> + 'a> {
+        Box::new(
+            std::iter::once(movement_state)
+                .chain(movement_state.synonym())
+                .flat_map(move |initial_movement_state| {
+                    tile_dict
+                        .get(&initial_movement_state.grid_coord)
+                        .into_iter()
+                        .flat_map(move |tile| {
+                            let action = tile.action;
+                            Self::iter_candidate_routes(initial_movement_state.anchor, action)
+                                .filter_map(move |route| {
+                                    route_evaluation_counter.increment();
+                                    route
+                                        .fragments_requirement
+                                        .is_subset(&tile.fragments)
+                                        .then_some(())?;
+                                    Some((
+                                        MovementState {
+                                            grid_coord: initial_movement_state.grid_coord,
+                                            anchor: route.terminal_anchor.act(action),
+                                        },
+                                        route.motion_primitive,
+                                        route
+                                            .pivotal_motion
+                                            .clone()
+                                            .rotate_global(Self::rotation_matrix_from_action(
+                                                action,
+                                            ))
+                                            .translate_global(
+                                                initial_movement_state.grid_coord.grid_position(),
+                                            ),
+                                    ))
+                                })
+                        })
+                })
+                .flat_map(
+                    move |(terminal_movement_state, motion_primitive, pivotal_motion)| {
+                        terminal_movement_state
+                            .anchor
+                            .stationery
+                            .then(|| {
+                                Box::new(std::iter::once(MovementTarget {
+                                    movement_state: terminal_movement_state,
+                                    transform: pivotal_motion.target(),
+                                    pivotal_motions: Vec::new(),
+                                    motion_primitives: Vec::new(),
+                                }))
+                                    as Box<dyn Iterator<Item = MovementTarget>>
+                            })
+                            .unwrap_or_else(|| {
+                                Self::iter_next_movement_targets_from(
+                                    terminal_movement_state,
+                                    tile_dict,
+                                    route_evaluation_counter,
+                                )
+                            })
+                            .into_iter()
+                            .map(move |successive_movement_target| MovementTarget {
+                                pivotal_motions: std::iter::once(pivotal_motion.clone())
+                                    .chain(successive_movement_target.pivotal_motions)
+                                    .collect(),
+                                motion_primitives: std::iter::once(motion_primitive)
+                                    .chain(successive_movement_target.motion_primitives)
+                                    .collect(),
+                                ..successive_movement_target
+                            })
+                    },
+                )
+                .filter(move |movement_target| {
+                    std::iter::once(movement_state)
+                        .chain(movement_state.synonym())
+                        .all(|initial_movement_state| {
+                            initial_movement_state != movement_target.movement_state
+                        })
+                }),
+        )
+    }
+
+    fn cached_targets(&self) -> std::sync::Arc<[MovementTarget]> {
+        self.target_cache.get_or_compute(self.movement_state, || {
+            self.iter_next_movement_targets().collect()
+        })
+    }
+
+    fn invalidate_cached_targets(&mut self) {
+        self.target_cache.clear();
+    }
+
+    pub fn iter_next_movement_targets(&self) -> Box<dyn Iterator<Item = MovementTarget> + '_> {
+        self.iter_player_movement_targets(PlayerId::PRIMARY)
+    }
+
+    fn iter_player_movement_targets(
+        &self,
+        player_id: PlayerId,
+    ) -> Box<dyn Iterator<Item = MovementTarget> + '_> {
+        self.route_evaluation_counter.reset();
+        let Some(player) = self.player(player_id) else {
+            return Box::new(std::iter::empty());
+        };
+        let occupied_coords: HashSet<_> = self
+            .iter_player_ids()
+            .filter(|&other_player_id| self.player_collision && other_player_id != player_id)
+            .filter_map(|other_player_id| self.player(other_player_id))
+            .flat_map(|other_player| {
+                std::iter::once(other_player.movement_state)
+                    .chain(other_player.movement_state.synonym())
+                    .map(|movement_state| movement_state.grid_coord)
+            })
+            .collect();
+        Box::new(
+            Self::iter_next_movement_targets_from(
+                player.movement_state,
+                &self.tile_dict,
+                &self.route_evaluation_counter,
+            )
+            .filter(move |movement_target| {
+                std::iter::once(movement_target.movement_state)
+                    .chain(movement_target.movement_state.synonym())
+                    .all(|movement_state| !occupied_coords.contains(&movement_state.grid_coord))
+            })
+            .filter(|movement_target| {
+                !self.collides_with_bounds(movement_target.movement_state.grid_coord)
+            }),
+        )
+    }
+
+    fn player(&self, player_id: PlayerId) -> Option<Player> {
+        match player_id.0 {
+            0 => Some(Player {
+                movement_state: self.movement_state,
+                transform: self.player_transform,
+            }),
+            index => self.co_players.get(index - 1).cloned(),
+        }
+    }
+
+    pub fn iter_player_ids(&self) -> impl Iterator<Item = PlayerId> {
+        (0..=self.co_players.len()).map(PlayerId)
+    }
+
+    pub fn movement_state(&self) -> MovementState {
+        self.movement_state
+    }
+
+    pub fn player_coord(&self) -> GridCoord {
+        GridCoord::from_position(self.player_transform.transform_point3(Vec3::ZERO))
+    }
+
+    pub fn player_movement_state(&self, player_id: PlayerId) -> Option<MovementState> {
+        self.player(player_id).map(|player| player.movement_state)
+    }
+
+    // The transform of a player resting at the given state, taken from the end
+    // of any route leading there.
+    fn movement_state_transform(&self, movement_state: MovementState) -> Option<Mat4> {
+        std::iter::once(movement_state)
+            .chain(movement_state.synonym())
+            .find_map(|movement_state| {
+                let tile = self.tile_dict.get(&movement_state.grid_coord)?;
+                ROUTE_LIST.iter().find_map(|route| {
+                    (route.fragments_requirement.is_subset(&tile.fragments)
+                        && route.terminal_anchor.act(tile.action) == movement_state.anchor)
+                        .then(|| {
+                            route
+                                .pivotal_motion
+                                .clone()
+                                .rotate_global(Self::rotation_matrix_from_action(tile.action))
+                                .translate_global(movement_state.grid_coord.grid_position())
+                                .target()
+                        })
+                })
+            })
+    }
+
+    pub fn add_player(&mut self, movement_state: MovementState) -> Option<PlayerId> {
+        let transform = self.movement_state_transform(movement_state)?;
+        self.co_players.push(Player {
+            movement_state,
+            transform,
+        });
+        self.invalidate_cached_targets();
+        Some(PlayerId(self.co_players.len()))
+    }
+
+    pub fn set_player_collision(&mut self, player_collision: bool) {
+        self.player_collision = player_collision;
+        self.invalidate_cached_targets();
+    }
+
+    pub fn set_play_bounds(&mut self, play_bounds: Option<(GridCoord, GridCoord)>) {
+        self.play_bounds = play_bounds;
+        self.invalidate_cached_targets();
+    }
+
+    pub fn collides_with_bounds(&self, coord: GridCoord) -> bool {
+        self.play_bounds
+            .is_some_and(|(GridCoord(min), GridCoord(max))| {
+                coord.0.cmplt(min).any() || coord.0.cmpgt(max).any()
+            })
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn last_route_evaluations(&self) -> usize {
+        self.route_evaluation_counter.get()
+    }
+
+    // How often the cached movement targets were computed afresh.
+    #[cfg(feature = "metrics")]
+    pub fn target_computations(&self) -> usize {
+        self.target_cache.computation_counter.get()
+    }
+
+    pub fn iter_coords(&self) -> impl Iterator<Item = GridCoord> + '_ {
+        self.tile_dict.keys().cloned()
+    }
+
+    pub fn preview_with_tile(
+        &self,
+        coord: GridCoord,
+        fragments: impl IntoIterator<Item = TileFragment>,
+        action: D6,
+    ) -> Vec<MovementState> {
+        let mut grid = self.clone();
+        grid.tile_dict.insert(
+            coord,
+            Tile {
+                fragments: fragments.into_iter().collect(),
+                action,
+            },
+        );
+        grid.iter_next_movement_targets()
+            .map(|movement_target| movement_target.movement_state)
+            .collect()
+    }
+
+    pub fn is_occupied(&self, coord: GridCoord) -> bool {
+        self.tile_dict.contains_key(&coord)
+    }
+
+    pub fn neighbors(&self, coord: GridCoord) -> Vec<GridCoord> {
+        coord
+            .iter_neighbor_coords()
+            .filter(|&neighbor_coord| self.is_occupied(neighbor_coord))
+            .collect()
+    }
+
+    // Triangles are bare plane pieces, while ladder and arch fragments are cut
+    // from a solid body filling part of the tile.
+    pub fn is_solid(&self, coord: GridCoord) -> bool {
+        self.tile_dict.get(&coord).is_some_and(|tile| {
+            tile.fragments
+                .iter()
+                .any(|fragment| fragment.recipe() != FragmentRecipe::Triangle)
+        })
+    }
+
+    pub fn conformal_transform(vector: Vec3) -> Vec2 {
+        CONFORMAL_PROJECTION_MATRIX.mul_vec3(vector).xy()
+    }
+
+    // Distance towards the viewer, along the projection axis (1, 1, 1).
+    fn conformal_depth(vector: Vec3) -> f32 {
+        CONFORMAL_PROJECTION_MATRIX.row(2).dot(vector)
+    }
+
+    // The projection as seen through the view action, which turns the scene in
+    // front of the camera without moving any tile.
+    pub fn view_transform(&self, vector: Vec3) -> Vec2 {
+        Self::conformal_transform(self.view_action.into_mat3() * vector)
+    }
+
+    fn view_depth(&self, vector: Vec3) -> f32 {
+        Self::conformal_depth(self.view_action.into_mat3() * vector)
+    }
+
+    // The direction towards the viewer in grid space.
+    fn view_direction(&self) -> Vec3 {
+        self.view_action.into_mat3().transpose() * VIEW_DIRECTION
+    }
+
+    pub fn view_action(&self) -> D6 {
+        self.view_action
+    }
+
+    // Only the rotations are accepted; the reflections send the projection
+    // axis (1, 1, 1) to its opposite and would show the scene from below.
+    pub fn set_view_action(&mut self, action: D6) -> Result<(), ViewError> {
+        if !action.is_rotation() {
+            return Err(ViewError::UpsideDown(action));
+        }
+        self.view_action = action;
+        Ok(())
+    }
+
+    // Turns the view further by `action`, on top of the current view action.
+    pub fn rotate_view(&mut self, action: D6) -> Result<(), ViewError> {
+        if !action.is_rotation() {
+            return Err(ViewError::UpsideDown(action));
+        }
+        self.view_action = action * self.view_action;
+        Ok(())
+    }
+
+    // Lifts a projected coordinate back to the point at the given view depth,
+    // undoing `view_transform` for cursor picking.
+    pub fn inverse_conformal_transform(&self, coord: Vec2, depth: f32) -> Vec3 {
+        self.view_action.into_mat3().transpose()
+            * CONFORMAL_PROJECTION_MATRIX
+                .transpose()
+                .mul_vec3(coord.extend(depth))
+    }
+
+    // Back to front. Tile centers all lie in the plane of zero depth, so ties
+    // fall back to the projected height, drawing higher tiles in front.
+    pub fn tiles_in_draw_order(&self) -> Vec<GridCoord> {
+        let mut coords: Vec<_> = self.iter_coords().collect();
+        coords.sort_by(|coord_0, coord_1| {
+            let (position_0, position_1) = (coord_0.grid_position(), coord_1.grid_position());
+            self.view_depth(position_0)
+                .total_cmp(&self.view_depth(position_1))
+                .then(
+                    self.view_transform(position_0)
+                        .y
+                        .total_cmp(&self.view_transform(position_1).y),
+                )
+                .then(coord_0.0.to_array().cmp(&coord_1.0.to_array()))
+        });
+        coords
+    }
+
+    // Takes the polygons either owned or borrowed from a scratch buffer.
+    fn iter_shapes_from_polygons<'a>(
+        &self,
+        polygons: impl IntoIterator<Item = impl Borrow<Polygon>> + 'a,
+    ) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + 'a {
+        let view_matrix = self.view_action.into_mat3();
+        polygons.into_iter().map(move |polygon| {
+            let polygon = polygon.borrow();
+            (
+                polygon
+                    .vertices
+                    .iter()
+                    .map(|&vertex| Self::conformal_transform(view_matrix * vertex))
+                    .collect(),
+                view_matrix * polygon.normal,
+            )
+        })
+    }
+
+    pub fn tile_orientation(&self, coord: GridCoord) -> Option<Mat4> {
+        self.tile_dict.get(&coord).map(|tile| {
+            Mat4::from_translation(coord.grid_position())
+                * Mat4::from_mat3(Self::rotation_matrix_from_action(tile.action))
+        })
+    }
+
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut coords: Vec<_> = self.iter_coords().collect();
+        coords.sort_by_key(|coord| coord.0.to_array());
+        let validation_errors: Vec<_> = coords
+            .into_iter()
+            .flat_map(|coord| {
+                self.tile_dict[&coord]
+                    .conflicting_fragment_pairs()
+                    .into_iter()
+                    .map(move |(fragment_0, fragment_1)| {
+                        ValidationError::IncompatibleFragments(coord, fragment_0, fragment_1)
+                    })
+            })
+            .collect();
+        if validation_errors.is_empty() {
+            Ok(())
+        } else {
+            Err(validation_errors)
+        }
+    }
+
+    // Returns the adjacent tile pairs where a route on the first tile hands the
+    // player over to the second one, but the second tile presents no fragment at
+    // all along their shared boundary to carry on with.
+    pub fn audit_boundary_consistency(&self) -> Vec<(GridCoord, GridCoord)> {
+        let mut inconsistent_pairs: Vec<_> = self
+            .tile_dict
+            .iter()
+            .flat_map(|(&coord, tile)| {
+                ROUTE_LIST
+                    .iter()
+                    .filter(|route| route.fragments_requirement.is_subset(&tile.fragments))
+                    .filter_map(move |route| {
+                        let handover_movement_state = MovementState {
+                            grid_coord: coord,
+                            anchor: route.terminal_anchor.act(tile.action),
+                        }
+                        .synonym()
+                        .filter(|movement_state| !movement_state.anchor.stationery)?;
+                        let neighbor_coord = handover_movement_state.grid_coord;
+                        let neighbor_tile = self.tile_dict.get(&neighbor_coord)?;
+                        ROUTE_LIST
+                            .iter()
+                            .filter(|neighbor_route| {
+                                neighbor_route
+                                    .fragments_requirement
+                                    .is_subset(&neighbor_tile.fragments)
+                            })
+                            .all(|neighbor_route| {
+                                neighbor_route
+                                    .initial_anchor
+                                    .act(neighbor_tile.action)
+                                    .external_position()
+                                    != handover_movement_state.anchor.external_position()
+                            })
+                            .then_some((coord, neighbor_coord))
+                    })
+            })
+            .collect();
+        inconsistent_pairs.sort_by_key(|(coord, neighbor_coord)| {
+            (coord.0.to_array(), neighbor_coord.0.to_array())
+        });
+        inconsistent_pairs.dedup();
+        inconsistent_pairs
+    }
+
+    // Finds the action that, composed onto the tile at `b`, lets it carry on
+    // some route the tile at `a` hands over to it. The identity comes first, so
+    // already connected tiles yield `D6::R0`.
+    pub fn connecting_action_delta(&self, a: GridCoord, b: GridCoord) -> Option<D6> {
+        let tile = self.tile_dict.get(&a)?;
+        let neighbor_tile = self.tile_dict.get(&b)?;
+        let handover_positions: Vec<_> = ROUTE_LIST
+            .iter()
+            .filter(|route| route.fragments_requirement.is_subset(&tile.fragments))
+            .filter_map(|route| {
+                MovementState {
+                    grid_coord: a,
+                    anchor: route.terminal_anchor.act(tile.action),
+                }
+                .synonym()
+                .filter(|movement_state| {
+                    !movement_state.anchor.stationery && movement_state.grid_coord == b
+                })
+                .and_then(|movement_state| movement_state.anchor.external_position())
+            })
+            .collect();
+        D6::iter_all().find(|&action_delta| {
+            ROUTE_LIST
+                .iter()
+                .filter(|neighbor_route| {
+                    neighbor_route
+                        .fragments_requirement
+                        .is_subset(&neighbor_tile.fragments)
+                })
+                .filter_map(|neighbor_route| {
+                    neighbor_route
+                        .initial_anchor
+                        .act(action_delta * neighbor_tile.action)
+                        .external_position()
+                })
+                .any(|position| handover_positions.contains(&position))
+        })
+    }
+
+    pub fn tile_centroid(&self) -> Option<Vec3> {
+        (!self.tile_dict.is_empty()).then(|| {
+            self.iter_coords()
+                .map(|coord| coord.grid_position())
+                .sum::<Vec3>()
+                / self.tile_dict.len() as f32
+        })
+    }
+
+    pub fn projected_tile_centroid(&self) -> Option<Vec2> {
+        self.tile_centroid()
+            .map(|tile_centroid| self.view_transform(tile_centroid))
+    }
+
+    // Fragments are stored in the local frame of the tile. Plane triangles are
+    // redrawn as their image under the tile action; the rest are rotated in
+    // place.
+    fn tile_fragment_placement(
+        coord: GridCoord,
+        tile: &Tile,
+        tile_fragment: TileFragment,
+    ) -> (TileFragment, Mat4) {
+        let translation = Mat4::from_translation(coord.grid_position());
+        match tile_fragment.plane_triangle_position() {
+            Some(_) => (tile_fragment.act(tile.action), translation),
+            None => (
+                tile_fragment,
+                translation * Mat4::from_mat3(Self::rotation_matrix_from_action(tile.action)),
+            ),
+        }
+    }
+
+    fn tile_fragment_polygons_into(
+        coord: GridCoord,
+        tile: &Tile,
+        tile_fragment: TileFragment,
+        transform: Mat4,
+        out: &mut Polygons,
+    ) {
+        let (placed_tile_fragment, matrix) =
+            Self::tile_fragment_placement(coord, tile, tile_fragment);
+        POLYGONS_DICT[&placed_tile_fragment].transform_into(transform * matrix, out);
+    }
+
+    // Writes the polygons of the tile fragments passing the predicate over `out`
+    // from `start` on, and returns the index after the last one written.
+    fn tile_polygons_filtered_into_at(
+        &self,
+        coord: GridCoord,
+        predicate: impl Fn(TileFragment) -> bool,
+        out: &mut Polygons,
+        start: usize,
+    ) -> usize {
+        let tile_transform = self.tile_transform(coord);
+        self.tile_dict.get(&coord).map_or(start, |tile| {
+            tile.fragments
+                .iter()
+                .filter(|&&tile_fragment| predicate(tile_fragment))
+                .fold(start, |start, &tile_fragment| {
+                    let (placed_tile_fragment, matrix) =
+                        Self::tile_fragment_placement(coord, tile, tile_fragment);
+                    POLYGONS_DICT[&placed_tile_fragment].transform_into_at(
+                        tile_transform * matrix,
+                        out,
+                        start,
+                    )
+                })
+        })
+    }
+
+    // Fills `out` with the fragment polygons of the tile as drawn, reusing the
+    // polygons already in it, so a buffer kept across frames stops allocating
+    // once it has grown to the largest tile.
+    pub fn tile_polygons_into(&self, coord: GridCoord, out: &mut Polygons) {
+        let end = self.tile_polygons_filtered_into_at(coord, |_| true, out, 0);
+        out.0.truncate(end);
+    }
+
+    fn all_tile_polygons_into(&self, out: &mut Polygons) {
+        let end = self.iter_coords().fold(0, |start, coord| {
+            self.tile_polygons_filtered_into_at(coord, |_| true, out, start)
+        });
+        out.0.truncate(end);
+    }
+
+    // Leaves `out` empty when there is no such player.
+    fn player_polygons_into(&self, player_id: PlayerId, out: &mut Polygons) {
+        match self.player(player_id) {
+            Some(player) => PLAYER_POLYGONS.transform_into(player.transform, out),
+            None => out.0.clear(),
+        }
+    }
+
+    pub fn set_tile_transform(&mut self, coord: GridCoord, transform: Mat4) {
+        self.tile_transform_dict.insert(coord, transform);
+    }
+
+    pub fn clear_tile_transform(&mut self, coord: GridCoord) {
+        self.tile_transform_dict.remove(&coord);
+    }
+
+    fn tile_transform(&self, coord: GridCoord) -> Mat4 {
+        self.tile_transform_dict
+            .get(&coord)
+            .copied()
+            .unwrap_or(Mat4::IDENTITY)
+    }
+
+    // The shape iterators fill a scratch buffer with one tile's polygons at a
+    // time. The `_with_scratch` variants borrow it from the caller, who can keep
+    // it across frames so that its polygons stop reallocating; the others own
+    // one of their own.
+    pub fn iter_tile_fragment_shapes(
+        &self,
+        coord: GridCoord,
+    ) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + '_ {
+        self.iter_tile_fragment_shapes_filtered(coord, |_| true)
+    }
+
+    pub fn iter_tile_fragment_shapes_with_scratch<'a>(
+        &'a self,
+        coord: GridCoord,
+        scratch: &'a mut Polygons,
+    ) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + 'a {
+        self.iter_tile_fragment_shapes_filtered_with_scratch(coord, |_| true, scratch)
+    }
+
+    // The predicate sees the fragments as listed on the tile, before the tile
+    // action is applied.
+    pub fn iter_tile_fragment_shapes_filtered<'a>(
+        &'a self,
+        coord: GridCoord,
+        predicate: impl Fn(TileFragment) -> bool + 'a,
+    ) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + 'a {
+        self.iter_tile_shapes_in([coord], predicate, |_| true, Polygons::default())
+    }
+
+    pub fn iter_tile_fragment_shapes_filtered_with_scratch<'a>(
+        &'a self,
+        coord: GridCoord,
+        predicate: impl Fn(TileFragment) -> bool + 'a,
+        scratch: &'a mut Polygons,
+    ) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + 'a {
+        self.iter_tile_shapes_in([coord], predicate, |_| true, scratch)
+    }
+
+    // Drops the polygons facing away from the viewer, judged by their normals.
+    pub fn iter_tile_fragment_shapes_visible(
+        &self,
+        coord: GridCoord,
+    ) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + '_ {
+        self.iter_tile_shapes_in([coord], |_| true, self.front_facing(), Polygons::default())
+    }
+
+    pub fn iter_tile_fragment_shapes_visible_with_scratch<'a>(
+        &'a self,
+        coord: GridCoord,
+        scratch: &'a mut Polygons,
+    ) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + 'a {
+        self.iter_tile_shapes_in([coord], |_| true, self.front_facing(), scratch)
+    }
+
+    pub fn iter_all_fragment_shapes_visible(&self) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + '_ {
+        self.iter_tile_shapes_in(
+            self.iter_coords(),
+            |_| true,
+            self.front_facing(),
+            Polygons::default(),
+        )
+    }
+
+    pub fn iter_all_fragment_shapes_visible_with_scratch<'a>(
+        &'a self,
+        scratch: &'a mut Polygons,
+    ) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + 'a {
+        self.iter_tile_shapes_in(self.iter_coords(), |_| true, self.front_facing(), scratch)
+    }
+
+    pub fn iter_all_fragment_shapes(&self) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + '_ {
+        self.iter_tile_shapes_in(self.iter_coords(), |_| true, |_| true, Polygons::default())
+    }
+
+    pub fn iter_all_fragment_shapes_with_scratch<'a>(
+        &'a self,
+        scratch: &'a mut Polygons,
+    ) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + 'a {
+        self.iter_tile_shapes_in(self.iter_coords(), |_| true, |_| true, scratch)
+    }
+
+    pub fn iter_all_fragment_shape_chunks(
+        &self,
+        chunk_size: usize,
+    ) -> impl Iterator<Item = Vec<(Vec<Vec2>, Vec3)>> + '_ {
+        Self::chunk_shapes(self.iter_all_fragment_shapes(), chunk_size)
+    }
+
+    pub fn iter_all_fragment_shape_chunks_with_scratch<'a>(
+        &'a self,
+        chunk_size: usize,
+        scratch: &'a mut Polygons,
+    ) -> impl Iterator<Item = Vec<(Vec<Vec2>, Vec3)>> + 'a {
+        Self::chunk_shapes(
+            self.iter_all_fragment_shapes_with_scratch(scratch),
+            chunk_size,
+        )
+    }
+
+    fn chunk_shapes(
+        mut shapes_iter: impl Iterator<Item = (Vec<Vec2>, Vec3)>,
+        chunk_size: usize,
+    ) -> impl Iterator<Item = Vec<(Vec<Vec2>, Vec3)>> {
+        assert!(chunk_size > 0);
+        std::iter::from_fn(move || {
+            let chunk: Vec<_> = shapes_iter.by_ref().take(chunk_size).collect();
+            (!chunk.is_empty()).then_some(chunk)
+        })
+    }
+
+    fn front_facing(&self) -> impl Fn(&Polygon) -> bool {
+        let view_direction = self.view_direction();
+        move |polygon| polygon.normal.dot(view_direction) > 0.0
+    }
+
+    // Refills the scratch with the next tile's polygons only once the shapes of
+    // the previous tile are used up, so it never holds more than the largest
+    // tile. Polygons past the current tile stay in place to be overwritten.
+    fn iter_tile_shapes_in<'a>(
+        &'a self,
+        coords: impl IntoIterator<Item = GridCoord> + 'a,
+        predicate: impl Fn(TileFragment) -> bool + 'a,
+        keep: impl Fn(&Polygon) -> bool + 'a,
+        mut scratch: impl BorrowMut<Polygons> + 'a,
+    ) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + 'a {
+        let mut coords = coords.into_iter();
+        let (mut index, mut end) = (0, 0);
+        std::iter::from_fn(move || loop {
+            let scratch = scratch.borrow_mut();
+            if index < end {
+                let polygon = &scratch.0[index];
+                index += 1;
+                if keep(polygon) {
+                    return self.iter_shapes_from_polygons([polygon]).next();
+                }
+            } else {
+                end = self.tile_polygons_filtered_into_at(coords.next()?, &predicate, scratch, 0);
+                index = 0;
+            }
+        })
+    }
+
+    fn iter_shapes_from_scratch<'a>(
+        &'a self,
+        scratch: impl Borrow<Polygons> + 'a,
+    ) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + 'a {
+        (0..scratch.borrow().0.len()).map(move |index| {
+            self.iter_shapes_from_polygons([&scratch.borrow().0[index]])
+                .next()
+                .unwrap()
+        })
+    }
+
+    // Each face fragment contributes the closed body on its bulk side, so the
+    // mesh is watertight as long as no two bodies touch. Vertices are shared by
+    // exact position, with -0.0 taken as 0.0, and transient tile transforms are
+    // ignored since they do not move the logical tiles.
+    pub fn solid_mesh(&self) -> (Vec<Vec3>, Vec<u32>) {
+        let mut positions = Vec::new();
+        let mut index_dict: HashMap<[u32; 3], u32> = HashMap::new();
+        let mut polygons = Polygons::default();
+        let end = self.tile_dict.iter().fold(0, |start, (&coord, tile)| {
+            tile.fragments
+                .iter()
+                .filter(|tile_fragment| matches!(tile_fragment.recipe(), FragmentRecipe::Face(_)))
+                .fold(start, |start, &tile_fragment| {
+                    let (placed_tile_fragment, matrix) =
+                        Self::tile_fragment_placement(coord, tile, tile_fragment);
+                    SOLID_POLYGONS_DICT[&placed_tile_fragment].transform_into_at(
+                        matrix,
+                        &mut polygons,
+                        start,
+                    )
+                })
+        });
+        let indices = polygons.0[..end]
+            .iter()
+            .flat_map(Polygon::triangulate)
+            .flatten()
+            .map(|position| {
+                *index_dict
+                    .entry(
+                        position
+                            .to_array()
+                            .map(|component| (component + 0.0).to_bits()),
+                    )
+                    .or_insert_with(|| {
+                        positions.push(position);
+                        positions.len() as u32 - 1
+                    })
+            })
+            .collect();
+        (positions, indices)
+    }
+
+    pub fn set_motion_config(&mut self, motion_config: MotionConfig) {
+        self.motion_config = motion_config;
+    }
+
+    pub fn set_frame_style(&mut self, frame_style: FrameStyle) {
+        self.frame_style = frame_style;
+    }
+
+    fn tile_frame_polygons(&self, coord: GridCoord) -> Polygons {
+        frame_polygons(self.frame_style.thickness)
+            .transform(self.tile_transform(coord) * Mat4::from_translation(coord.grid_position()))
+    }
+
+    pub fn iter_tile_frame_shapes(
+        &self,
+        coord: GridCoord,
+    ) -> impl Iterator<Item = (Vec<Vec2>, Vec3, [f32; 4])> + '_ {
+        self.tile_dict.get(&coord).into_iter().flat_map(move |_| {
+            self.iter_shapes_from_polygons(self.tile_frame_polygons(coord).0)
+                .map(|(shape, normal)| (shape, normal, self.frame_style.color))
+        })
+    }
+
+    // Frames around the tiles the next move can end on, other than the tile the
+    // player stands on, for the caller to draw in a highlight color.
+    pub fn iter_reachable_coord_shapes(&self) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + '_ {
+        let mut coords: Vec<_> = self
+            .cached_targets()
+            .iter()
+            .map(|movement_target| movement_target.movement_state.canonical().grid_coord)
+            .filter(|&coord| coord != self.movement_state.canonical().grid_coord)
+            .filter(|&coord| self.is_occupied(coord))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        coords.sort_by_key(|coord| coord.0.to_array());
+        coords.into_iter().flat_map(move |coord| {
+            self.iter_shapes_from_polygons(self.tile_frame_polygons(coord).0)
+        })
+    }
+
+    pub fn iter_player_shapes(
+        &self,
+        player_id: PlayerId,
+    ) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + '_ {
+        let mut polygons = Polygons::default();
+        self.player_polygons_into(player_id, &mut polygons);
+        self.iter_shapes_from_scratch(polygons)
+    }
+
+    pub fn iter_player_shapes_with_scratch<'a>(
+        &'a self,
+        player_id: PlayerId,
+        scratch: &'a mut Polygons,
+    ) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + 'a {
+        self.player_polygons_into(player_id, scratch);
+        self.iter_shapes_from_scratch(scratch)
+    }
+
+    // Back to front by the depth of the polygon centroids. Ties are broken by
+    // kind and then by projected centroid, so that the order does not depend on
+    // the iteration order of `tile_dict`.
+    fn sorted_polygons(&self) -> Vec<(Polygon, ShapeKind)> {
+        let mut fragment_polygons = Polygons::default();
+        self.all_tile_polygons_into(&mut fragment_polygons);
+        let tile_polygons = fragment_polygons
+            .0
+            .into_iter()
+            .map(|polygon| (polygon, ShapeKind::Fragment))
+            .chain(
+                self.iter_coords()
+                    .flat_map(|coord| self.tile_frame_polygons(coord).0)
+                    .map(|polygon| (polygon, ShapeKind::Frame)),
+            );
+        let player_polygons = self
+            .iter_player_ids()
+            .flat_map(|player_id| {
+                let mut player_polygons = Polygons::default();
+                self.player_polygons_into(player_id, &mut player_polygons);
+                player_polygons.0
+            })
+            .map(|polygon| (polygon, ShapeKind::Player));
+        let marker_polygons = self
+            .iter_marker_polygons()
+            .map(|polygon| (polygon, ShapeKind::Marker));
+        let mut keyed_polygons: Vec<_> = tile_polygons
+            .chain(player_polygons)
+            .chain(marker_polygons)
+            .map(|(polygon, shape_kind)| {
+                let centroid = polygon.centroid();
+                (
+                    self.view_depth(centroid),
+                    self.view_transform(centroid),
+                    polygon,
+                    shape_kind,
+                )
+            })
+            .collect();
+        keyed_polygons.sort_by(
+            |(depth_0, coord_0, _, kind_0), (depth_1, coord_1, _, kind_1)| {
+                depth_0
+                    .total_cmp(depth_1)
+                    .then(kind_0.cmp(kind_1))
+                    .then(coord_0.y.total_cmp(&coord_1.y))
+                    .then(coord_0.x.total_cmp(&coord_1.x))
+            },
+        );
+        keyed_polygons
+            .into_iter()
+            .map(|(_, _, polygon, shape_kind)| (polygon, shape_kind))
+            .collect()
+    }
+
+    pub fn iter_all_shapes_sorted(&self) -> Vec<(Vec<Vec2>, Vec3, ShapeKind)> {
+        self.sorted_polygons()
+            .into_iter()
+            .map(|(polygon, shape_kind)| {
+                let (shape, normal) = self.iter_shapes_from_polygons([polygon]).next().unwrap();
+                (shape, normal, shape_kind)
+            })
+            .collect()
+    }
+
+    fn player_shadow_polygons(&self) -> Polygons {
+        let ground_z = self
+            .iter_coords()
+            .map(|coord| coord.grid_position().z)
+            .min_by(f32::total_cmp)
+            .unwrap_or(0.0);
+        Polygons(
+            PLAYER_POLYGONS
+                .0
+                .iter()
+                .map(|polygon| Polygon {
+                    vertices: polygon
+                        .vertices
+                        .iter()
+                        .map(|&vertex| {
+                            self.player_transform
+                                .transform_point3(vertex)
+                                .with_z(ground_z)
+                        })
+                        .collect(),
+                    normal: Vec3::Z,
+                })
+                .collect(),
+        )
+    }
+
+    pub fn iter_player_shadow_shapes(&self) -> impl Iterator<Item = Vec<Vec2>> + '_ {
+        self.iter_shapes_from_polygons(self.player_shadow_polygons().0)
+            .map(|(shape, _)| shape)
+    }
+
+    pub fn iter_marker_polygons(&self) -> impl Iterator<Item = Polygon> + '_ {
+        self.cached_targets()
+            .to_vec()
+            .into_iter()
+            .flat_map(|movement_target| {
+                MARKER_POLYGONS
+                    .clone()
+                    .transform(movement_target.transform)
+                    .0
+            })
+    }
+
+    // Poses along the path to each marker, starting from the current pose and
+    // stepping `spacing` along the trajectory, so the last step may be shorter
+    // to land on the marker itself. A spacing that is not positive previews
+    // nothing.
+    pub fn iter_marker_previews(
+        &self,
+        spacing: f32,
+    ) -> impl Iterator<Item = (MovementState, Vec<Mat4>)> + '_ {
+        let movement_targets = if spacing > 0.0 {
+            self.cached_targets().to_vec()
+        } else {
+            Vec::new()
+        };
+        movement_targets.into_iter().map(move |movement_target| {
+            let mut trajectory =
+                PivotalMotionTrajectory::from_pivotal_motions(movement_target.pivotal_motions);
+            let mut transforms: Vec<_> = trajectory.peek().into_iter().collect();
+            while let (Some(transform), unused_distance) =
+                trajectory.consume_distance_tracked(spacing)
+            {
+                transforms.push(transform);
+                if unused_distance > 0.0 {
+                    break;
+                }
+            }
+            (movement_target.movement_state, transforms)
+        })
+    }
+
+    pub fn iter_marker_shapes(&self) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + '_ {
+        self.iter_shapes_from_polygons(self.iter_marker_polygons())
+    }
+
+    fn iter_connecting_pivotal_motions(
+        &self,
+        coord: GridCoord,
+        neighbor_coord: GridCoord,
+    ) -> impl Iterator<Item = PivotalMotion> + '_ {
+        self.tile_dict
+            .get(&coord)
+            .filter(|_| self.tile_dict.contains_key(&neighbor_coord))
+            .into_iter()
+            .flat_map(move |tile| {
+                ROUTE_LIST.iter().filter_map(move |route| {
+                    route
+                        .fragments_requirement
+                        .is_subset(&tile.fragments)
+                        .then_some(())?;
+                    let TileAnchorPositionAxis::External(external_position, _) =
+                        route.initial_anchor.act(tile.action).position_axis
+                    else {
+                        return None;
+                    };
+                    (coord.add_offset(external_position.into_offset()) == neighbor_coord)
+                        .then_some(())?;
+                    Some(
+                        route
+                            .pivotal_motion
+                            .clone()
+                            .rotate_global(Self::rotation_matrix_from_action(tile.action))
+                            .translate_global(coord.grid_position()),
+                    )
+                })
+            })
+    }
+
+    pub fn iter_connection_shapes(
+        &self,
+        coord_0: GridCoord,
+        coord_1: GridCoord,
+    ) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + '_ {
+        const SAMPLE_DISTANCE: f32 = 0.1;
+        self.iter_connecting_pivotal_motions(coord_0, coord_1)
+            .chain(self.iter_connecting_pivotal_motions(coord_1, coord_0))
+            .map(|pivotal_motion| {
+                let mut trajectory =
+                    PivotalMotionTrajectory::from_pivotal_motions(Vec::from([pivotal_motion]));
+                let vertices: Vec<_> = std::iter::once(0.0)
+                    .chain(std::iter::repeat(SAMPLE_DISTANCE))
+                    .map_while(|distance| trajectory.consume_distance(distance))
+                    .map(|transform| transform.transform_point3(Vec3::ZERO))
+                    .collect();
+                // The plane the path sweeps through. A path has no front side,
+                // so the normal is turned towards the viewer.
+                let normal = vertices
+                    .iter()
+                    .zip(vertices.iter().cycle().skip(1))
+                    .map(|(vertex, next_vertex)| vertex.cross(*next_vertex))
+                    .sum::<Vec3>()
+                    .normalize_or_zero();
+                let normal = if normal.dot(self.view_direction()) < 0.0 {
+                    -normal
+                } else {
+                    normal
+                };
+                self.iter_shapes_from_polygons([Polygon { vertices, normal }])
+                    .next()
+                    .unwrap()
+            })
+    }
+
+    fn pending_movement_target(&self, cursor_coord: Vec2) -> Option<MovementTarget> {
+        let movement_targets = self.cached_targets().to_vec();
+        self.pick_movement_target(
+            self.player_transform,
+            movement_targets.into_iter(),
+            cursor_coord,
+        )
+    }
+
+    fn pending_player_movement_target(
+        &self,
+        player_id: PlayerId,
+        cursor_coord: Vec2,
+    ) -> Option<MovementTarget> {
+        let player_transform = self.player(player_id)?.transform;
+        self.pick_movement_target(
+            player_transform,
+            self.iter_player_movement_targets(player_id),
+            cursor_coord,
+        )
+    }
+
+    fn pick_movement_target(
+        &self,
+        player_transform: Mat4,
+        movement_targets: impl Iterator<Item = MovementTarget>,
+        cursor_coord: Vec2,
+    ) -> Option<MovementTarget> {
+        let MotionConfig {
+            radius_threshold,
+            angle_threshold,
+        } = self.motion_config;
+        movement_targets
+            .filter_map(|movement_target| {
+                let player_coord =
+                    self.view_transform(player_transform.transform_point3(Vec3::ZERO));
+                ((cursor_coord - player_coord).length() > radius_threshold).then_some(())?;
+                let target_coord =
+                    self.view_transform(movement_target.transform.transform_point3(Vec3::ZERO));
+                let abs_angle = (target_coord - player_coord)
+                    .angle_to(cursor_coord - player_coord)
+                    .abs();
+                (abs_angle < angle_threshold).then_some(())?;
+                let distance = (target_coord - player_coord).length();
+                Some((movement_target, abs_angle, distance))
+            })
+            .min_by(Self::compare_movement_target_candidates)
+            .map(|(movement_target, _, _)| movement_target)
+    }
+
+    // Orders by angle to the cursor, breaking ties by projected distance and
+    // then by target coordinate and position, so that the choice does not
+    // depend on the iteration order of `tile_dict`.
+    fn compare_movement_target_candidates(
+        (movement_target_0, abs_angle_0, distance_0): &(MovementTarget, f32, f32),
+        (movement_target_1, abs_angle_1, distance_1): &(MovementTarget, f32, f32),
+    ) -> std::cmp::Ordering {
+        let position_0 = movement_target_0.transform.transform_point3(Vec3::ZERO);
+        let position_1 = movement_target_1.transform.transform_point3(Vec3::ZERO);
+        abs_angle_0
+            .total_cmp(abs_angle_1)
+            .then(distance_0.total_cmp(distance_1))
+            .then_with(|| {
+                movement_target_0
+                    .movement_state
+                    .grid_coord
+                    .0
+                    .to_array()
+                    .cmp(&movement_target_1.movement_state.grid_coord.0.to_array())
+            })
+            .then_with(|| {
+                position_0
+                    .to_array()
+                    .into_iter()
+                    .zip(position_1.to_array())
+                    .map(|(component_0, component_1)| component_0.total_cmp(&component_1))
+                    .fold(std::cmp::Ordering::Equal, std::cmp::Ordering::then)
+            })
+    }
+
+    pub fn hover_preview_transform(&self, cursor_coord: Vec2) -> Option<Mat4> {
+        self.pending_movement_target(cursor_coord)
+            .map(|movement_target| movement_target.transform)
+    }
+
+    pub fn iter_hover_preview_shapes(
+        &self,
+        cursor_coord: Vec2,
+    ) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + '_ {
+        let mut polygons = Polygons::default();
+        self.hover_preview_polygons_into(cursor_coord, &mut polygons);
+        self.iter_shapes_from_scratch(polygons)
+    }
+
+    pub fn iter_hover_preview_shapes_with_scratch<'a>(
+        &'a self,
+        cursor_coord: Vec2,
+        scratch: &'a mut Polygons,
+    ) -> impl Iterator<Item = (Vec<Vec2>, Vec3)> + 'a {
+        self.hover_preview_polygons_into(cursor_coord, scratch);
+        self.iter_shapes_from_scratch(scratch)
+    }
+
+    // Leaves `out` empty when no move is pending under the cursor.
+    fn hover_preview_polygons_into(&self, cursor_coord: Vec2, out: &mut Polygons) {
+        match self.hover_preview_transform(cursor_coord) {
+            Some(transform) => PLAYER_POLYGONS.transform_into(transform, out),
+            None => out.0.clear(),
+        }
+    }
+
+    fn commit_movement_target(
+        &mut self,
+        movement_target: MovementTarget,
+    ) -> PivotalMotionTrajectory {
+        self.redo_stack.clear();
+        self.apply_movement_target(movement_target)
+    }
+
+    fn apply_movement_target(
+        &mut self,
+        movement_target: MovementTarget,
+    ) -> PivotalMotionTrajectory {
+        self.undo_stack.push(MoveRecord {
+            previous_movement_state: self.movement_state,
+            movement_target: movement_target.clone(),
+        });
+        self.trim_undo_stack();
+        self.movement_state = movement_target.movement_state;
+        self.move_history.push(movement_target.movement_state);
+        PivotalMotionTrajectory::from_pivotal_motions(movement_target.pivotal_motions)
+    }
+
+    pub fn set_history_limit(&mut self, history_limit: usize) {
+        self.history_limit = Some(history_limit);
+        self.trim_undo_stack();
+    }
+
+    fn trim_undo_stack(&mut self) {
+        if let Some(history_limit) = self.history_limit {
+            let excess = self.undo_stack.len().saturating_sub(history_limit);
+            self.undo_stack.drain(..excess);
+        }
+    }
+
+    // The returned trajectory walks the undone move backwards along its path.
+    pub fn undo(&mut self) -> Option<PivotalMotionTrajectory> {
+        let move_record = self.undo_stack.pop()?;
+        self.movement_state = move_record.previous_movement_state;
+        self.move_history.pop();
+        let trajectory = PivotalMotionTrajectory::from_pivotal_motions(
+            move_record.movement_target.pivotal_motions.clone(),
+        )
+        .reversed();
+        self.redo_stack.push(move_record);
+        Some(trajectory)
+    }
+
+    pub fn redo(&mut self) -> Option<PivotalMotionTrajectory> {
+        let move_record = self.redo_stack.pop()?;
+        Some(self.apply_movement_target(move_record.movement_target))
+    }
+
+    pub fn move_count(&self) -> usize {
+        self.move_history.len()
+    }
+
+    pub fn set_checkpoint(&mut self) {
+        self.checkpoint = Some(Checkpoint {
+            movement_state: self.movement_state,
+            player_transform: self.player_transform,
+            move_history: self.move_history.clone(),
+        });
+    }
+
+    // The player is placed at the checkpoint instantly, so the returned
+    // trajectory is empty; the move count rolls back to the checkpoint's.
+    pub fn restart_from_checkpoint(&mut self) -> Option<PivotalMotionTrajectory> {
+        let checkpoint = self.checkpoint.clone()?;
+        self.movement_state = checkpoint.movement_state;
+        self.player_transform = checkpoint.player_transform;
+        self.move_history = checkpoint.move_history;
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        Some(PivotalMotionTrajectory::from_pivotal_motions(Vec::new()))
+    }
+
+    pub fn motion_trajectory(&mut self, cursor_coord: Vec2) -> Option<PivotalMotionTrajectory> {
+        self.pending_movement_target(cursor_coord)
+            .map(|movement_target| self.commit_movement_target(movement_target))
+    }
+
+    // Co-players are not animated by the caller, so they are placed at the
+    // target right away; the trajectory is still returned for display.
+    pub fn player_motion_trajectory(
+        &mut self,
+        player_id: PlayerId,
+        cursor_coord: Vec2,
+    ) -> Option<PivotalMotionTrajectory> {
+        if player_id == PlayerId::PRIMARY {
+            return self.motion_trajectory(cursor_coord);
+        }
+        let movement_target = self.pending_player_movement_target(player_id, cursor_coord)?;
+        let player = &mut self.co_players[player_id.0 - 1];
+        player.movement_state = movement_target.movement_state;
+        player.transform = movement_target.transform;
+        self.invalidate_cached_targets();
+        Some(PivotalMotionTrajectory::from_pivotal_motions(
+            movement_target.pivotal_motions,
+        ))
+    }
+
+    fn find_movement_target(
+        mut movement_targets: impl Iterator<Item = MovementTarget>,
+        movement_state: MovementState,
+    ) -> Option<MovementTarget> {
+        movement_targets.find(|movement_target| {
+            std::iter::once(movement_state)
+                .chain(movement_state.synonym())
+                .any(|movement_state| movement_state == movement_target.movement_state)
+        })
+    }
+
+    pub fn motion_to(&mut self, movement_state: MovementState) -> Option<PivotalMotionTrajectory> {
+        let movement_target =
+            Self::find_movement_target(self.iter_next_movement_targets(), movement_state)?;
+        Some(self.commit_movement_target(movement_target))
+    }
+
+    // Dijkstra over movement states, where each move costs the sum of the costs
+    // of the route primitives it chains together. Unlisted primitives cost 1.
+    pub fn plan_path_weighted(
+        &self,
+        goal: MovementState,
+        route_costs: &HashMap<RouteMotionPrimitive, f32>,
+    ) -> Option<Vec<MovementState>> {
+        // Searches keep their own count, so `last_route_evaluations` still
+        // describes the latest movement target query.
+        let route_evaluation_counter = Counter::default();
+        let mut path_costs: HashMap<MovementState, (f32, Option<MovementState>)> =
+            HashMap::from([(self.movement_state, (0.0, None))]);
+        let mut open_movement_states = Vec::from([self.movement_state]);
+        let mut closed_movement_states = HashSet::new();
+        while let Some(index) = open_movement_states
+            .iter()
+            .enumerate()
+            .min_by(|(_, movement_state_0), (_, movement_state_1)| {
+                path_costs[*movement_state_0]
+                    .0
+                    .total_cmp(&path_costs[*movement_state_1].0)
+            })
+            .map(|(index, _)| index)
+        {
+            let movement_state = open_movement_states.swap_remove(index);
+            if std::iter::once(goal)
+                .chain(goal.synonym())
+                .any(|goal| goal == movement_state)
+            {
+                let mut path: Vec<_> =
+                    std::iter::successors(Some(movement_state), |movement_state| {
+                        path_costs[movement_state].1
+                    })
+                    .collect();
+                path.pop();
+                path.reverse();
+                return Some(path);
+            }
+            closed_movement_states
+                .extend(std::iter::once(movement_state).chain(movement_state.synonym()));
+            let path_cost = path_costs[&movement_state].0;
+            for movement_target in Self::iter_next_movement_targets_from(
+                movement_state,
+                &self.tile_dict,
+                &route_evaluation_counter,
+            ) {
+                let next_movement_state = movement_target.movement_state;
+                if closed_movement_states.contains(&next_movement_state) {
+                    continue;
+                }
+                let next_path_cost = path_cost
+                    + movement_target
+                        .motion_primitives
+                        .iter()
+                        .map(|motion_primitive| {
+                            route_costs.get(motion_primitive).copied().unwrap_or(1.0)
+                        })
+                        .sum::<f32>();
+                if path_costs
+                    .get(&next_movement_state)
+                    .is_none_or(|&(cost, _)| next_path_cost < cost)
+                {
+                    path_costs.insert(next_movement_state, (next_path_cost, Some(movement_state)));
+                    if !open_movement_states.contains(&next_movement_state) {
+                        open_movement_states.push(next_movement_state);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    // Follows `plan_path_weighted` with unit route costs, so the path takes the
+    // fewest route segments, as the hint does. The motions chain directly into a
+    // single trajectory.
+    pub fn path_to(&self, target: MovementState) -> Option<Vec<PivotalMotion>> {
+        let path = self.plan_path_weighted(target, &HashMap::new())?;
+        Some(
+            self.movement_targets_for_path(&path)?
+                .into_iter()
+                .flat_map(|movement_target| movement_target.pivotal_motions)
+                .collect(),
+        )
+    }
+
+    // Every state is stored in its canonical form, so the two encodings of an
+    // external anchor count once and cyclic levels still terminate.
+    pub fn reachable_states(&self) -> HashSet<MovementState> {
+        let route_evaluation_counter = Counter::default();
+        let mut reachable_states = HashSet::from([self.movement_state.canonical()]);
+        let mut open_movement_states = Vec::from([self.movement_state]);
+        while let Some(movement_state) = open_movement_states.pop() {
+            for movement_target in Self::iter_next_movement_targets_from(
+                movement_state,
+                &self.tile_dict,
+                &route_evaluation_counter,
+            ) {
+                if reachable_states.insert(movement_target.movement_state.canonical()) {
+                    open_movement_states.push(movement_target.movement_state);
+                }
+            }
+        }
+        reachable_states
+    }
+
+    // Nodes are the canonical reachable states, the start state drawn with a
+    // double outline.
+    pub fn movement_graph_dot(&self) -> String {
+        let route_evaluation_counter = Counter::default();
+        let mut movement_states: Vec<_> = self.reachable_states().into_iter().collect();
+        movement_states.sort_by_key(|movement_state| {
+            let (GridCoord(coord), anchor_key) = movement_state.anchor_key();
+            (coord.to_array(), anchor_key)
+        });
+        let node_index_dict: HashMap<_, _> = movement_states
+            .iter()
+            .enumerate()
+            .map(|(index, &movement_state)| (movement_state, index))
+            .collect();
+        let mut dot = String::from("digraph movement_graph {\n");
+        for (index, movement_state) in movement_states.iter().enumerate() {
+            dot.push_str(&format!(
+                "    n{index} [label=\"{} {:?} {:?}\"{}];\n",
+                movement_state.grid_coord.0,
+                movement_state.anchor.position_axis,
+                movement_state.anchor.sign,
+                if *movement_state == self.movement_state.canonical() {
+                    ", peripheries=2"
+                } else {
+                    ""
+                },
+            ));
+        }
+        for (index, &movement_state) in movement_states.iter().enumerate() {
+            for movement_target in Self::iter_next_movement_targets_from(
+                movement_state,
+                &self.tile_dict,
+                &route_evaluation_counter,
+            ) {
+                dot.push_str(&format!(
+                    "    n{index} -> n{};\n",
+                    node_index_dict[&movement_target.movement_state.canonical()]
+                ));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    // One object per tile, grouped by fragment kind plus the frame, and one
+    // object per player. Every polygon gets its own normal, and faces are
+    // written as n-gons.
+    pub fn to_obj(&self) -> String {
+        fn push_polygons(obj: &mut String, counts: &mut (usize, usize), polygons: &Polygons) {
+            for polygon in &polygons.0 {
+                for vertex in &polygon.vertices {
+                    obj.push_str(&format!("v {} {} {}\n", vertex.x, vertex.y, vertex.z));
+                }
+                let normal = polygon.normal.normalize();
+                obj.push_str(&format!("vn {} {} {}\n", normal.x, normal.y, normal.z));
+                let (vertex_count, normal_count) = counts;
+                *normal_count += 1;
+                obj.push('f');
+                for index in *vertex_count + 1..=*vertex_count + polygon.vertices.len() {
+                    obj.push_str(&format!(" {index}//{normal_count}"));
+                }
+                obj.push('\n');
+                *vertex_count += polygon.vertices.len();
+            }
+        }
+
+        let mut obj = String::new();
+        let mut counts = (0, 0);
+        let Ok(()) = self.try_for_each_obj_group(|obj_group, polygons| {
+            if let Some(object) = obj_group.object {
+                obj.push_str(&format!("o {object}\n"));
+            }
+            obj.push_str(&format!("g {}\n", obj_group.group));
+            push_polygons(&mut obj, &mut counts, polygons);
+            Ok::<_, std::convert::Infallible>(())
+        });
+        obj
+    }
+
+    // Walks the scene as drawn, in an order that does not depend on the hash
+    // maps: tiles by coordinate with their fragments by name and then their
+    // frame, followed by the players.
+    fn try_for_each_obj_group<E>(
+        &self,
+        mut visit: impl FnMut(ObjGroup, &Polygons) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let mut polygons = Polygons::default();
+        let mut coords: Vec<_> = self.iter_coords().collect();
+        coords.sort_by_key(|coord| coord.0.to_array());
+        for coord in coords {
+            let tile = &self.tile_dict[&coord];
+            let tile_transform = self.tile_transform(coord);
+            let mut object = Some(format!("tile_{}_{}_{}", coord.0.x, coord.0.y, coord.0.z));
+            let mut tile_fragments: Vec<_> = tile.fragments.iter().copied().collect();
+            tile_fragments.sort_by_key(|tile_fragment| format!("{tile_fragment:?}"));
+            for tile_fragment in tile_fragments {
+                Self::tile_fragment_polygons_into(
+                    coord,
+                    tile,
+                    tile_fragment,
+                    tile_transform,
+                    &mut polygons,
+                );
+                visit(
+                    ObjGroup {
+                        object: object.take(),
+                        group: format!("{tile_fragment:?}"),
+                    },
+                    &polygons,
+                )?;
+            }
+            visit(
+                ObjGroup {
+                    object,
+                    group: String::from("Frame"),
+                },
+                &self.tile_frame_polygons(coord),
+            )?;
+        }
+        for player_id in self.iter_player_ids() {
+            if self.player(player_id).is_some() {
+                self.player_polygons_into(player_id, &mut polygons);
+                visit(
+                    ObjGroup {
+                        object: Some(format!("player_{}", player_id.0)),
+                        group: String::from("Player"),
+                    },
+                    &polygons,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    // States are canonicalized, so a state and its synonym count as one.
+    pub fn reachability_diff(
+        &self,
+        other: &Self,
+    ) -> (HashSet<MovementState>, HashSet<MovementState>) {
+        let reachable_states = self.reachable_states();
+        let other_reachable_states = other.reachable_states();
+        (
+            reachable_states
+                .difference(&other_reachable_states)
+                .copied()
+                .collect(),
+            other_reachable_states
+                .difference(&reachable_states)
+                .copied()
+                .collect(),
+        )
+    }
+
+    pub fn set_goal(&mut self, goal: Option<MovementState>) {
+        self.goal = goal;
+    }
+
+    // Both encodings of an external anchor reach the goal. Without a goal
+    // there is nothing to solve.
+    pub fn is_solved(&self) -> bool {
+        self.goal
+            .is_some_and(|goal| goal.canonical() == self.movement_state.canonical())
+    }
+
+    pub fn is_solvable(&self) -> bool {
+        self.goal
+            .is_some_and(|goal| self.reachable_states().contains(&goal.canonical()))
+    }
+
+    // Route costs default to one, so the hint follows a path with the fewest
+    // route segments.
+    pub fn next_hint(&self) -> Option<MovementState> {
+        self.plan_path_weighted(self.goal?, &HashMap::new())?
+            .first()
+            .copied()
+    }
+
+    // difficulty = path_length * mean_branching + ln(1 + reachable_state_count)
+    // where the path is the shortest one to the goal, and the branching is the
+    // number of moves offered at each state the path leaves from. Without a goal
+    // only the size of the reachable region counts; an unreachable goal is
+    // infinitely hard.
+    pub fn difficulty_estimate(&self) -> f32 {
+        let route_evaluation_counter = Counter::default();
+        let exploration = (1.0 + self.reachable_states().len() as f32).ln();
+        let Some(goal) = self.goal else {
+            return exploration;
+        };
+        let Some(path) = self.plan_path_weighted(goal, &HashMap::new()) else {
+            return f32::INFINITY;
+        };
+        let branching_sum: usize = std::iter::once(self.movement_state)
+            .chain(path.iter().copied())
+            .take(path.len())
+            .map(|movement_state| {
+                Self::iter_next_movement_targets_from(
+                    movement_state,
+                    &self.tile_dict,
+                    &route_evaluation_counter,
+                )
+                .count()
+            })
+            .sum();
+        let mean_branching = branching_sum as f32 / path.len().max(1) as f32;
+        path.len() as f32 * mean_branching + exploration
+    }
+
+    fn movement_targets_for_path(&self, path: &[MovementState]) -> Option<Vec<MovementTarget>> {
+        let route_evaluation_counter = Counter::default();
+        path.iter()
+            .try_fold(
+                (self.movement_state, Vec::new()),
+                |(movement_state, mut movement_targets), &next_movement_state| {
+                    let movement_target = Self::find_movement_target(
+                        Self::iter_next_movement_targets_from(
+                            movement_state,
+                            &self.tile_dict,
+                            &route_evaluation_counter,
+                        ),
+                        next_movement_state,
+                    )?;
+                    let movement_state = movement_target.movement_state;
+                    movement_targets.push(movement_target);
+                    Some((movement_state, movement_targets))
+                },
+            )
+            .map(|(_, movement_targets)| movement_targets)
+    }
+
+    pub fn trajectory_for_path(&self, path: &[MovementState]) -> Option<PivotalMotionTrajectory> {
+        self.movement_targets_for_path(path)
+            .map(|movement_targets| {
+                movement_targets.into_iter().fold(
+                    PivotalMotionTrajectory::from_pivotal_motions(Vec::new()),
+                    |trajectory, movement_target| {
+                        trajectory.then(PivotalMotionTrajectory::from_pivotal_motions(
+                            movement_target.pivotal_motions,
+                        ))
+                    },
+                )
+            })
+    }
+
+    pub fn start_recording(&mut self) {
+        self.recording_start = Some(self.move_history.len());
+    }
+
+    // Moves undone past the start of the recording are left out of it.
+    pub fn stop_recording(&mut self) -> Replay {
+        Replay(
+            self.recording_start
+                .take()
+                .map(|start| self.move_history[start.min(self.move_history.len())..].to_vec())
+                .unwrap_or_default(),
+        )
+    }
+
+    pub fn play<'a>(
+        &'a mut self,
+        replay: &'a Replay,
+    ) -> impl Iterator<Item = PivotalMotionTrajectory> + 'a {
+        replay
+            .0
+            .iter()
+            .map_while(|&movement_state| self.motion_to(movement_state))
+    }
+
+    pub fn snapshot(&self) -> GridSnapshot {
+        GridSnapshot {
+            movement_state: self.movement_state,
+            player_transform: self.player_transform.to_cols_array(),
+            move_history: self.move_history.clone(),
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: &GridSnapshot) -> Result<(), RestoreError> {
+        let coord = snapshot.movement_state.grid_coord;
+        self.tile_dict
+            .contains_key(&coord)
+            .then_some(())
+            .ok_or(RestoreError::MissingTile(coord))?;
+        snapshot
+            .move_history
+            .last()
+            .is_none_or(|movement_state| *movement_state == snapshot.movement_state)
+            .then_some(())
+            .ok_or(RestoreError::HistoryMismatch)?;
+        self.movement_state = snapshot.movement_state;
+        self.player_transform = Mat4::from_cols_array(&snapshot.player_transform);
+        self.move_history = snapshot.move_history.clone();
+        // The motions of the moves are not part of the snapshot.
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        Ok(())
+    }
+
+    pub fn move_history(&self) -> &[MovementState] {
+        &self.move_history
+    }
+
+    // Replaying the export on the level as it started reproduces every move.
+    pub fn export_history(&self) -> Replay {
+        Replay(self.move_history.clone())
+    }
+
+    pub fn set_player_transform(&mut self, player_transform: Mat4) {
+        self.player_transform = player_transform;
+    }
+
+    pub fn write_scene_obj(&self, w: &mut impl std::io::Write) -> std::io::Result<()> {
+        fn write_polygons(
+            w: &mut impl std::io::Write,
+            material: &str,
+            polygons: &Polygons,
+            index_offset: &mut usize,
+        ) -> std::io::Result<()> {
+            writeln!(w, "usemtl {material}")?;
+            for polygon in &polygons.0 {
+                for triangle in polygon.triangulate() {
+                    for vertex in triangle {
+                        writeln!(w, "v {} {} {}", vertex.x, vertex.y, vertex.z)?;
+                    }
+                    let normal = polygon.normal.normalize_or_zero();
+                    writeln!(w, "vn {} {} {}", normal.x, normal.y, normal.z)?;
+                    let normal_index = *index_offset / 3 + 1;
+                    writeln!(
+                        w,
+                        "f {}//{normal_index} {}//{normal_index} {}//{normal_index}",
+                        *index_offset + 1,
+                        *index_offset + 2,
+                        *index_offset + 3,
+                    )?;
+                    *index_offset += 3;
+                }
+            }
+            Ok(())
+        }
+
+        let mut index_offset = 0;
+        self.try_for_each_obj_group(|obj_group, polygons| {
+            if let Some(object) = obj_group.object {
+                writeln!(w, "o {object}")?;
+            }
+            write_polygons(w, &obj_group.group, polygons, &mut index_offset)
+        })
+    }
+
+    // The leading fragment required by a route family is the one lying on its
+    // external boundary, e.g. the triangle cap of an extended ladder.
+    pub fn boundary_fragment(
+        &self,
+        coord: GridCoord,
+        position: TileExternalAnchorPosition,
+    ) -> Option<TileFragment> {
+        let tile = self.tile_dict.get(&coord)?;
+        ROUTE_FAMILY_INFO_LIST
+            .iter()
+            .filter(|route_family_info| {
+                route_family_info.external_position.act(tile.action) == position
+                    && route_family_info
+                        .fragments_requirement
+                        .iter()
+                        .all(|tile_fragment| tile.fragments.contains(tile_fragment))
+            })
+            .find_map(|route_family_info| route_family_info.fragments_requirement.first())
+            .copied()
+    }
+
+    pub fn same_level(&self, other: &Self) -> bool {
+        self.tile_dict == other.tile_dict
+            && self.movement_state == other.movement_state
+            && self.player_transform == other.player_transform
+    }
+
+    pub fn to_level(&self) -> LevelData {
+        let mut tiles: Vec<_> = self
+            .tile_dict
+            .iter()
+            .map(|(&coord, tile)| {
+                let mut fragments: Vec<_> = tile.fragments.iter().copied().collect();
+                fragments.sort_by_key(|&fragment| fragment as usize);
+                TileData {
+                    coord,
+                    fragments,
+                    action: tile.action,
+                }
+            })
+            .collect();
+        tiles.sort_by_key(|tile_data| tile_data.coord.0.to_array());
+        LevelData {
+            tiles,
+            movement_state: self.movement_state,
+            player_transform: self.player_transform,
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn from_json(s: &str) -> Result<Self, LoadError> {
+        Ok(Self::from_level(
+            serde_json::from_str(s).map_err(LoadError::Json)?,
+        )?)
+    }
+
+    #[cfg(feature = "compact")]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_level().to_bytes()
+    }
+
+    #[cfg(feature = "compact")]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, LoadError> {
+        Ok(Self::from_level(LevelData::from_bytes(bytes)?)?)
+    }
+
+    pub fn from_level(level_data: LevelData) -> Result<Self, LevelError> {
+        let mut tile_dict = HashMap::new();
+        for tile_data in level_data.tiles {
+            let mut fragments = HashSet::new();
+            for fragment in tile_data.fragments {
+                fragments
+                    .insert(fragment)
+                    .then_some(())
+                    .ok_or(LevelError::DuplicateFragment(tile_data.coord, fragment))?;
+            }
+            tile_dict
+                .insert(
+                    tile_data.coord,
+                    Tile {
+                        fragments,
+                        action: tile_data.action,
+                    },
+                )
+                .is_none()
+                .then_some(())
+                .ok_or(LevelError::DuplicateTile(tile_data.coord))?;
+        }
+        let start_coord = level_data.movement_state.grid_coord;
+        tile_dict
+            .contains_key(&start_coord)
+            .then_some(())
+            .ok_or(LevelError::MissingStartTile(start_coord))?;
+        Ok(Self::from_parts(
+            tile_dict,
+            level_data.movement_state,
+            level_data.player_transform,
+        ))
+    }
+
+    fn from_parts(
+        tile_dict: HashMap<GridCoord, Tile>,
+        movement_state: MovementState,
+        player_transform: Mat4,
+    ) -> Self {
+        Self {
+            tile_dict,
+            movement_state,
+            player_transform,
+            route_evaluation_counter: Counter::default(),
+            recording_start: None,
+            frame_style: FrameStyle::default(),
+            motion_config: MotionConfig::default(),
+            co_players: Vec::new(),
+            player_collision: false,
+            play_bounds: None,
+            goal: None,
+            tile_transform_dict: HashMap::new(),
+            checkpoint: None,
+            move_history: Vec::new(),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            history_limit: None,
+            target_cache: TargetCache::default(),
+            view_action: D6::R0,
+        }
+    }
+
+    // Checks turning the tile by `by`. The player anchor is expressed in grid
+    // coordinates, so reorienting the tile underneath would leave the player
+    // detached from its fragments. The fragments as drawn afterwards must still
+    // be able to share the tile.
+    fn validate_tile(&self, coord: GridCoord, by: D6) -> Result<(), EditError> {
+        let tile = self
+            .tile_dict
+            .get(&coord)
+            .ok_or(EditError::MissingTile(coord))?;
+        std::iter::once(self.movement_state)
+            .chain(self.movement_state.synonym())
+            .all(|movement_state| movement_state.grid_coord != coord)
+            .then_some(())
+            .ok_or(EditError::OccupiedTile(coord))?;
+        let action = tile.action * by;
+        Tile {
+            fragments: tile
+                .fragments
+                .iter()
+                .map(|tile_fragment| tile_fragment.act(action))
+                .collect(),
+            action,
+        }
+        .conflicting_fragment_pairs()
+        .is_empty()
+        .then_some(())
+        .ok_or(EditError::ConflictingFragments(coord))
+    }
+
+    pub fn rotate_tile(&mut self, coord: GridCoord, by: D6) -> Result<(), EditError> {
+        self.validate_tile(coord, by)?;
+        self.invalidate_cached_targets();
+        let tile = self.tile_dict.get_mut(&coord).unwrap();
+        tile.action = tile.action * by;
+        Ok(())
+    }
+
+    // Rotates about the origin tile, which is fixed by every action.
+    pub fn rotate_level(&self, action: D6) -> Self {
+        let transform = Mat4::from_mat3(Self::rotation_matrix_from_action(action));
+        Self {
+            tile_dict: self
+                .tile_dict
+                .iter()
+                .map(|(&coord, tile)| {
+                    (
+                        coord.act(action),
+                        Tile {
+                            fragments: tile.fragments.clone(),
+                            action: action * tile.action,
+                        },
+                    )
+                })
+                .collect(),
+            movement_state: self.movement_state.act(action),
+            player_transform: transform * self.player_transform,
+            route_evaluation_counter: Counter::default(),
+            recording_start: self.recording_start,
+            frame_style: self.frame_style,
+            motion_config: self.motion_config,
+            co_players: self
+                .co_players
+                .iter()
+                .map(|player| Player {
+                    movement_state: player.movement_state.act(action),
+                    transform: transform * player.transform,
+                })
+                .collect(),
+            player_collision: self.player_collision,
+            play_bounds: self.play_bounds.map(|(min, max)| {
+                let (min, max) = (min.act(action), max.act(action));
+                (GridCoord(min.0.min(max.0)), GridCoord(min.0.max(max.0)))
+            }),
+            goal: self.goal.map(|goal| goal.act(action)),
+            tile_transform_dict: self
+                .tile_transform_dict
+                .iter()
+                .map(|(&coord, &tile_transform)| {
+                    (
+                        coord.act(action),
+                        transform * tile_transform * transform.inverse(),
+                    )
+                })
+                .collect(),
+            checkpoint: self.checkpoint.as_ref().map(|checkpoint| Checkpoint {
+                movement_state: checkpoint.movement_state.act(action),
+                player_transform: transform * checkpoint.player_transform,
+                move_history: checkpoint
+                    .move_history
+                    .iter()
+                    .map(|movement_state| movement_state.act(action))
+                    .collect(),
+            }),
+            move_history: self
+                .move_history
+                .iter()
+                .map(|movement_state| movement_state.act(action))
+                .collect(),
+            // Recorded motions cannot follow improper actions, so the rotated
+            // level starts with fresh undo and redo stacks.
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            history_limit: self.history_limit,
+            target_cache: TargetCache::default(),
+            view_action: self.view_action,
+        }
+    }
+
+    // The actions reached by repeatedly turning by `D6::R1`, starting from
+    // `action` itself.
+    pub fn action_orbit(action: D6) -> Vec<D6> {
+        (0..D6::R1.order() as i32)
+            .map(|k| action * D6::R1.pow(k))
+            .collect()
+    }
+
+    pub fn cycle_tile_action(&mut self, coord: GridCoord) -> Result<D6, EditError> {
+        self.validate_tile(coord, D6::R1)?;
+        self.invalidate_cached_targets();
+        let tile = self.tile_dict.get_mut(&coord).unwrap();
+        tile.action = Self::action_orbit(tile.action)[1];
+        Ok(tile.action)
+    }
+
+    pub fn flip_tile(&mut self, coord: GridCoord) -> Result<(), EditError> {
+        self.validate_tile(coord, D6::S0)?;
+        self.invalidate_cached_targets();
+        let tile = self.tile_dict.get_mut(&coord).unwrap();
+        tile.action = tile.action * D6::S0;
+        Ok(())
+    }
+}
+
+lazy_static::lazy_static! {
+    pub static ref WORLD_LIST: Vec<Grid> = vec![
+        Grid {
+            tile_dict: map_macro::hash_map! {
+                GridCoord::new(0, 0, 0) => Tile {
+                    fragments: map_macro::hash_set! {
                         TileFragment::TriangleZForeLeft,
                         TileFragment::TriangleZForeRight,
                         TileFragment::TriangleZSideLeft,
@@ -800,31 +3340,130 @@ lazy_static::lazy_static! {
                 },
                 GridCoord::new(0, 1, -1) => Tile {
                     fragments: map_macro::hash_set! {
-                        TileFragment::TriangleZForeLeft,
-                        TileFragment::TriangleZForeRight,
-                        TileFragment::TriangleZSideLeft,
+                        TileFragment::TriangleZForeLeft,
+                        TileFragment::TriangleZForeRight,
+                        TileFragment::TriangleZSideLeft,
+                        TileFragment::TriangleZSideRight,
+                        TileFragment::TriangleZRearLeft,
+                        TileFragment::TriangleZRearRight,
+                    },
+                    action: D6::R0,
+                },
+                GridCoord::new(-1, 0, 1) => Tile {
+                    fragments: map_macro::hash_set! {
+                        TileFragment::TriangleZForeLeft,
+                        TileFragment::TriangleZForeRight,
+                        TileFragment::TriangleZSideLeft,
+                        TileFragment::TriangleZSideRight,
+                        TileFragment::TriangleZRearLeft,
+                        TileFragment::TriangleZRearRight,
+                    },
+                    action: D6::R0,
+                },
+                GridCoord::new(1, -1, 0) => Tile {
+                    fragments: map_macro::hash_set! {
+                        TileFragment::TriangleZForeLeft,
+                        TileFragment::TriangleZForeRight,
+                        TileFragment::TriangleZSideLeft,
+                        TileFragment::TriangleZSideRight,
+                        TileFragment::TriangleZRearLeft,
+                        TileFragment::TriangleZRearRight,
+                    },
+                    action: D6::R0,
+                },
+            },
+            movement_state: MovementState {
+                grid_coord: GridCoord::new(0, 0, 0),
+                anchor: TileAnchor {
+                    position_axis: TileAnchorPositionAxis::Internal(
+                        TileInternalAnchorPositionAxis::PlaneForeZ,
+                    ),
+                    sign: TileAnchorSign::Pos,
+                    stationery: true,
+                },
+            },
+            player_transform: Mat4::from_translation(Vec3::new(1.0, 1.0, 0.0)),
+            route_evaluation_counter: Counter::default(),
+            recording_start: None,
+            frame_style: FrameStyle::default(),
+            motion_config: MotionConfig::default(),
+            co_players: Vec::new(),
+            player_collision: false,
+            play_bounds: None,
+            goal: None,
+            tile_transform_dict: HashMap::new(),
+            checkpoint: None,
+            move_history: Vec::new(),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            history_limit: None,
+            target_cache: TargetCache::default(),
+            view_action: D6::R0,
+        },
+        Grid {
+            tile_dict: map_macro::hash_map! {
+                GridCoord::new(0, 0, 0) => Tile {
+                    fragments: map_macro::hash_set! {
+                        TileFragment::TriangleZSideLeft,
+                        TileFragment::TriangleZRearLeft,
+                        TileFragment::TriangleZRearRight,
+                    },
+                    action: D6::R0,
+                },
+                GridCoord::new(-1, 0, 1) => Tile {
+                    fragments: map_macro::hash_set! {
+                        TileFragment::LadderMajorFace
+                    },
+                    action: D6::R1,
+                },
+                GridCoord::new(-2, 0, 2) => Tile {
+                    fragments: map_macro::hash_set! {
+                        TileFragment::TriangleZForeLeft,
+                        TileFragment::TriangleZForeRight,
+                        TileFragment::TriangleZSideLeft,
+                    },
+                    action: D6::R0,
+                },
+                GridCoord::new(-1, -1, 2) => Tile {
+                    fragments: map_macro::hash_set! {
+                        TileFragment::TriangleZSideRight,
+                        TileFragment::TriangleZRearLeft,
+                        TileFragment::TriangleZRearRight,
+                    },
+                    action: D6::R0,
+                },
+                GridCoord::new(-1, -2, 3) => Tile {
+                    fragments: map_macro::hash_set! {
+                        TileFragment::TriangleZForeLeft,
+                        TileFragment::TriangleZForeRight,
+                        TileFragment::TriangleZSideLeft,
+                    },
+                    action: D6::R0,
+                },
+                GridCoord::new(0, -3, 3) => Tile {
+                    fragments: map_macro::hash_set! {
+                        TileFragment::TriangleXRear,
+                        TileFragment::TriangleYFore,
+                        TileFragment::ArchMinorFace,
+                    },
+                    action: D6::R2,
+                },
+                GridCoord::new(1, -3, 2) => Tile {
+                    fragments: map_macro::hash_set! {
                         TileFragment::TriangleZSideRight,
                         TileFragment::TriangleZRearLeft,
                         TileFragment::TriangleZRearRight,
                     },
-                    action: D6::R0,
+                    action: D6::R5,
                 },
-                GridCoord::new(-1, 0, 1) => Tile {
+                GridCoord::new(1, -2, 1) => Tile {
                     fragments: map_macro::hash_set! {
-                        TileFragment::TriangleZForeLeft,
-                        TileFragment::TriangleZForeRight,
-                        TileFragment::TriangleZSideLeft,
-                        TileFragment::TriangleZSideRight,
-                        TileFragment::TriangleZRearLeft,
-                        TileFragment::TriangleZRearRight,
+                        TileFragment::ArchMajorFace,
                     },
-                    action: D6::R0,
+                    action: D6::R2,
                 },
                 GridCoord::new(1, -1, 0) => Tile {
                     fragments: map_macro::hash_set! {
-                        TileFragment::TriangleZForeLeft,
-                        TileFragment::TriangleZForeRight,
-                        TileFragment::TriangleZSideLeft,
                         TileFragment::TriangleZSideRight,
                         TileFragment::TriangleZRearLeft,
                         TileFragment::TriangleZRearRight,
@@ -843,106 +3482,2505 @@ lazy_static::lazy_static! {
                 },
             },
             player_transform: Mat4::from_translation(Vec3::new(1.0, 1.0, 0.0)),
+            route_evaluation_counter: Counter::default(),
+            recording_start: None,
+            frame_style: FrameStyle::default(),
+            motion_config: MotionConfig::default(),
+            co_players: Vec::new(),
+            player_collision: false,
+            play_bounds: None,
+            goal: None,
+            tile_transform_dict: HashMap::new(),
+            checkpoint: None,
+            move_history: Vec::new(),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            history_limit: None,
+            target_cache: TargetCache::default(),
+            view_action: D6::R0,
+        },
+    ];
+}
+
+#[test]
+fn test() {
+    let world = &WORLD_LIST[0];
+    world
+        .iter_next_movement_targets()
+        // .skip(0)
+        // .next()
+        .for_each(|movement_target| {
+            dbg!(movement_target.clone());
+            let mut pivotal_motion_path =
+                PivotalMotionTrajectory::from_pivotal_motions(movement_target.pivotal_motions);
+            while let Some(transform) = pivotal_motion_path.consume_distance(0.4) {
+                dbg!(transform.transform_point3(Vec3::ZERO));
+            }
+        });
+}
+
+#[cfg(test)]
+fn iter_route_anchors(
+    grid: &Grid,
+    coord: GridCoord,
+) -> impl Iterator<Item = (TileAnchor, TileAnchor)> + '_ {
+    let tile = grid.tile_dict.get(&coord).unwrap();
+    ROUTE_LIST
+        .iter()
+        .filter(|route| route.fragments_requirement.is_subset(&tile.fragments))
+        .map(|route| {
+            (
+                route.initial_anchor.act(tile.action),
+                route.terminal_anchor.act(tile.action),
+            )
+        })
+}
+
+#[test]
+fn test_flip_tile() {
+    let ladder_coord = GridCoord::new(-1, 0, 1);
+    let mut world = WORLD_LIST[1].clone();
+    let anchors: Vec<_> = iter_route_anchors(&world, ladder_coord).collect();
+    world.flip_tile(ladder_coord).unwrap();
+    let flipped_anchors: Vec<_> = iter_route_anchors(&world, ladder_coord).collect();
+    assert_eq!(world.tile_dict[&ladder_coord].action, D6::R1 * D6::S0);
+    assert_eq!(
+        flipped_anchors,
+        anchors
+            .iter()
+            .map(|&(initial_anchor, terminal_anchor)| {
+                // The local reflection S0 seen from the global frame of an R1-rotated tile.
+                let reflection = D6::R1 * D6::S0 * D6::R5;
+                (
+                    initial_anchor.act(reflection),
+                    terminal_anchor.act(reflection),
+                )
+            })
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        world.flip_tile(GridCoord::new(5, 5, -10)),
+        Err(EditError::MissingTile(GridCoord::new(5, 5, -10)))
+    );
+    assert_eq!(
+        world.flip_tile(GridCoord::new(0, 0, 0)),
+        Err(EditError::OccupiedTile(GridCoord::new(0, 0, 0)))
+    );
+    assert_eq!(world.tile_dict[&GridCoord::new(0, 0, 0)].action, D6::R0);
+
+    // Flipping is the same edit as rotating the tile by S0.
+    let mut rotated_world = WORLD_LIST[1].clone();
+    rotated_world.rotate_tile(ladder_coord, D6::S0).unwrap();
+    assert_eq!(rotated_world.tile_dict, world.tile_dict);
+}
+
+#[test]
+fn test_movement_state_synonym() {
+    let internal_movement_state = WORLD_LIST[0].movement_state;
+    assert!(internal_movement_state.synonym().is_none());
+    let external_movement_state = MovementState {
+        grid_coord: GridCoord::new(0, 0, 0),
+        anchor: TileAnchor {
+            position_axis: TileAnchorPositionAxis::External(
+                TileExternalAnchorPosition::ForeLeft,
+                TileExternalAnchorAxis::Z,
+            ),
+            sign: TileAnchorSign::Pos,
+            stationery: false,
+        },
+    };
+    let synonym = external_movement_state.synonym().unwrap();
+    assert_eq!(synonym.grid_coord, GridCoord::new(1, 0, -1));
+    assert_eq!(
+        synonym.anchor.position_axis,
+        TileAnchorPositionAxis::External(
+            TileExternalAnchorPosition::RearRight,
+            TileExternalAnchorAxis::Z
+        ),
+    );
+    assert_eq!(synonym.synonym(), Some(external_movement_state));
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+fn test_route_evaluation_counter() {
+    let world = WORLD_LIST[0].clone();
+    world.iter_next_movement_targets().for_each(drop);
+    let route_evaluations = world.last_route_evaluations();
+    assert!(route_evaluations > 0);
+    // Searches over the whole level leave the latest query's count alone.
+    world.reachable_states();
+    world.movement_graph_dot();
+    world.difficulty_estimate();
+    world.plan_path_weighted(world.movement_state, &HashMap::new());
+    assert_eq!(world.last_route_evaluations(), route_evaluations);
+
+    let mut isolated_world = world.clone();
+    isolated_world
+        .tile_dict
+        .retain(|&coord, _| coord == GridCoord::new(0, 0, 0));
+    isolated_world.iter_next_movement_targets().for_each(drop);
+    assert!(isolated_world.last_route_evaluations() > 0);
+    assert!(isolated_world.last_route_evaluations() < route_evaluations);
+}
+
+#[test]
+fn test_hover_preview_transform() {
+    let mut world = WORLD_LIST[0].clone();
+    let player_coord =
+        Grid::conformal_transform(world.player_transform.transform_point3(Vec3::ZERO));
+    let target_transform = world.iter_next_movement_targets().next().unwrap().transform;
+    let target_coord = Grid::conformal_transform(target_transform.transform_point3(Vec3::ZERO));
+    let cursor_coord = player_coord + 4.0 * (target_coord - player_coord);
+    let preview_transform = world.hover_preview_transform(cursor_coord).unwrap();
+    assert_eq!(
+        world.iter_hover_preview_shapes(cursor_coord).count(),
+        PLAYER_POLYGONS.0.len()
+    );
+
+    let mut trajectory = world.motion_trajectory(cursor_coord).unwrap();
+    let mut committed_transform = world.player_transform;
+    while let Some(transform) = trajectory.consume_distance(1e-3) {
+        committed_transform = transform;
+    }
+    assert!(committed_transform.abs_diff_eq(preview_transform, 1e-3));
+}
+
+#[test]
+fn test_route_initial_anchors_achievable() {
+    const ACTIONS: [D6; 12] = [
+        D6::R0,
+        D6::R1,
+        D6::R2,
+        D6::R3,
+        D6::R4,
+        D6::R5,
+        D6::S0,
+        D6::S1,
+        D6::S2,
+        D6::S3,
+        D6::S4,
+        D6::S5,
+    ];
+    let achievable_anchors: HashSet<TileAnchor> = ROUTE_LIST
+        .iter()
+        .flat_map(|route| {
+            ACTIONS.into_iter().flat_map(|action| {
+                let movement_state = MovementState {
+                    grid_coord: GridCoord::new(0, 0, 0),
+                    anchor: route.terminal_anchor.act(action),
+                };
+                std::iter::once(movement_state)
+                    .chain(movement_state.synonym())
+                    .map(|movement_state| movement_state.anchor)
+            })
+        })
+        .chain(WORLD_LIST.iter().map(|world| world.movement_state.anchor))
+        .collect();
+    let dead_routes: Vec<_> = ROUTE_LIST
+        .iter()
+        .enumerate()
+        .filter(|(_, route)| {
+            ACTIONS
+                .into_iter()
+                .all(|action| !achievable_anchors.contains(&route.initial_anchor.act(action)))
+        })
+        .map(|(i, route)| (i, route.initial_anchor))
+        .collect();
+    assert!(dead_routes.is_empty(), "{dead_routes:#?}");
+}
+
+#[test]
+fn test_iter_connection_shapes() {
+    let world = &WORLD_LIST[1];
+    let ground_coord = GridCoord::new(0, 0, 0);
+    let ladder_coord = GridCoord::new(-1, 0, 1);
+    let connection_shapes: Vec<_> = world
+        .iter_connection_shapes(ground_coord, ladder_coord)
+        .collect();
+    assert!(!connection_shapes.is_empty());
+    assert!(connection_shapes.iter().all(|(path, _)| path.len() > 1));
+    // Every sampled point lies in the plane of the returned normal.
+    world
+        .iter_connecting_pivotal_motions(ground_coord, ladder_coord)
+        .chain(world.iter_connecting_pivotal_motions(ladder_coord, ground_coord))
+        .zip(connection_shapes.iter())
+        .for_each(|(pivotal_motion, (_, normal))| {
+            assert!((normal.length() - 1.0).abs() < 1e-4);
+            assert!(normal.dot(VIEW_DIRECTION) >= 0.0);
+            let mut trajectory =
+                PivotalMotionTrajectory::from_pivotal_motions(Vec::from([pivotal_motion]));
+            let start = trajectory
+                .consume_distance(0.0)
+                .unwrap()
+                .transform_point3(Vec3::ZERO);
+            while let Some(transform) = trajectory.consume_distance(0.1) {
+                assert!(
+                    normal
+                        .dot(transform.transform_point3(Vec3::ZERO) - start)
+                        .abs()
+                        < 1e-3
+                );
+            }
+        });
+    // The ladder and the tile beside its top are adjacent, but no route links them.
+    let side_coord = GridCoord::new(-1, -1, 2);
+    assert!(world.tile_dict.contains_key(&side_coord));
+    assert_eq!(
+        world
+            .iter_connection_shapes(ladder_coord, side_coord)
+            .count(),
+        0
+    );
+}
+
+#[test]
+fn test_angle_cot_angle() {
+    assert_eq!(RouteMotionPrimitive::angle_cot_angle(0.0), 1.0);
+    assert_eq!(
+        RouteMotionPrimitive::angle_cot_angle(GEOMETRY_EPSILON / 2.0),
+        1.0
+    );
+    assert!(
+        (RouteMotionPrimitive::angle_cot_angle(std::f32::consts::FRAC_PI_4)
+            - std::f32::consts::FRAC_PI_4)
+            .abs()
+            < 1e-6
+    );
+}
+
+#[test]
+fn test_tile_orientation() {
+    let world = &WORLD_LIST[1];
+    let ladder_coord = GridCoord::new(-1, 0, 1);
+    let orientation = world.tile_orientation(ladder_coord).unwrap();
+    let (_, rotation, translation) = orientation.to_scale_rotation_translation();
+    assert!(
+        Mat3::from_mat4(orientation).abs_diff_eq(Grid::rotation_matrix_from_action(D6::R1), 1e-6)
+    );
+    assert!(translation.abs_diff_eq(ladder_coord.grid_position(), 1e-6));
+    assert!(rotation.is_finite());
+    assert!(world.tile_orientation(GridCoord::new(3, 3, -6)).is_none());
+}
+
+#[test]
+fn test_iter_all_fragment_shape_chunks() {
+    let mut world = WORLD_LIST[0].clone();
+    let tile = world.tile_dict[&GridCoord::new(0, 0, 0)].clone();
+    world.tile_dict = (-30..30)
+        .flat_map(|x| (-30..30).map(move |y| GridCoord::new(x, y, -x - y)))
+        .map(|coord| (coord, tile.clone()))
+        .collect();
+    let shape_count = world.iter_all_fragment_shapes().count();
+    assert_eq!(shape_count, 3600 * 6);
+    let chunk_sizes: Vec<_> = world
+        .iter_all_fragment_shape_chunks(1000)
+        .map(|chunk| chunk.len())
+        .collect();
+    assert_eq!(chunk_sizes.iter().sum::<usize>(), shape_count);
+    assert!(chunk_sizes.iter().all(|&chunk_size| chunk_size <= 1000));
+
+    // Only one tile's polygons are ever held, however many chunks are drawn.
+    let tile_polygon_count = world
+        .iter_tile_fragment_shapes(GridCoord::new(0, 0, 0))
+        .count();
+    let mut scratch = Polygons::default();
+    let mut chunks = world.iter_all_fragment_shape_chunks_with_scratch(1000, &mut scratch);
+    assert_eq!(chunks.next().map(|chunk| chunk.len()), Some(1000));
+    drop(chunks);
+    assert!(scratch.0.len() <= tile_polygon_count);
+    assert!(scratch.0.capacity() <= 2 * tile_polygon_count);
+}
+
+#[test]
+fn test_player_shadow() {
+    let world = &WORLD_LIST[1];
+    let shadow_polygons = world.player_shadow_polygons();
+    let player_polygons = PLAYER_POLYGONS.clone().transform(world.player_transform);
+    assert_eq!(shadow_polygons.0.len(), player_polygons.0.len());
+    shadow_polygons.0.iter().zip(&player_polygons.0).for_each(
+        |(shadow_polygon, player_polygon)| {
+            shadow_polygon
+                .vertices
+                .iter()
+                .zip(&player_polygon.vertices)
+                .for_each(|(shadow_vertex, player_vertex)| {
+                    assert_eq!(shadow_vertex.z, 0.0);
+                    assert_eq!(shadow_vertex.xy(), player_vertex.xy());
+                });
+        },
+    );
+    assert_eq!(
+        world.iter_player_shadow_shapes().count(),
+        PLAYER_POLYGONS.0.len()
+    );
+}
+
+#[test]
+fn test_movement_target_tie_breaking() {
+    let movement_target = |grid_coord: GridCoord, position: Vec3| MovementTarget {
+        movement_state: MovementState {
+            grid_coord,
+            ..WORLD_LIST[0].movement_state
+        },
+        transform: Mat4::from_translation(position),
+        pivotal_motions: Vec::new(),
+        motion_primitives: Vec::new(),
+    };
+    let candidate_0 = (
+        movement_target(GridCoord::new(0, 1, -1), Vec3::new(-1.0, 1.0, 0.0)),
+        0.1,
+        2.0,
+    );
+    let candidate_1 = (
+        movement_target(GridCoord::new(1, 0, -1), Vec3::new(1.0, -1.0, 0.0)),
+        0.1,
+        2.0,
+    );
+    let select = |candidates: [&(MovementTarget, f32, f32); 2]| {
+        candidates
+            .into_iter()
+            .min_by(|a, b| Grid::compare_movement_target_candidates(a, b))
+            .unwrap()
+            .0
+            .movement_state
+    };
+    assert_eq!(
+        select([&candidate_0, &candidate_1]),
+        select([&candidate_1, &candidate_0])
+    );
+    assert_eq!(
+        select([&candidate_0, &candidate_1]),
+        candidate_0.0.movement_state
+    );
+}
+
+#[test]
+fn test_write_scene_obj() {
+    let world = &WORLD_LIST[1];
+    let mut buffer = Vec::new();
+    world.write_scene_obj(&mut buffer).unwrap();
+    let obj = String::from_utf8(buffer).unwrap();
+    let count_triangles = |polygons: &Polygons| -> usize {
+        polygons
+            .0
+            .iter()
+            .map(|polygon| polygon.triangulate().len())
+            .sum()
+    };
+    let expected_face_count = world
+        .tile_dict
+        .values()
+        .map(|tile| {
+            tile.fragments
+                .iter()
+                .map(|tile_fragment| count_triangles(&POLYGONS_DICT[tile_fragment]))
+                .sum::<usize>()
+                + count_triangles(&frame_polygons(world.frame_style.thickness))
+        })
+        .sum::<usize>()
+        + count_triangles(&PLAYER_POLYGONS);
+    assert_eq!(
+        obj.lines().filter(|line| line.starts_with("f ")).count(),
+        expected_face_count
+    );
+    assert_eq!(
+        obj.lines().filter(|line| line.starts_with("v ")).count(),
+        3 * expected_face_count
+    );
+    assert_eq!(
+        obj.lines()
+            .filter(|line| line.starts_with("o tile_"))
+            .count(),
+        world.tile_dict.len()
+    );
+    assert!(obj.lines().any(|line| line == "usemtl ArchMajorFace"));
+
+    // The same scene in a freshly built map exports the same file.
+    let mut rebuilt_world = world.clone();
+    rebuilt_world.tile_dict = world.tile_dict.clone().into_iter().collect();
+    let mut rebuilt_buffer = Vec::new();
+    rebuilt_world.write_scene_obj(&mut rebuilt_buffer).unwrap();
+    assert_eq!(String::from_utf8(rebuilt_buffer).unwrap(), obj);
+
+    // Tile transforms move the exported tile with the drawn one.
+    let mut moved_world = world.clone();
+    moved_world.set_tile_transform(
+        GridCoord::new(0, 0, 0),
+        Mat4::from_translation(Vec3::new(0.0, 0.0, 100.0)),
+    );
+    let mut moved_buffer = Vec::new();
+    moved_world.write_scene_obj(&mut moved_buffer).unwrap();
+    let max_z = |obj: &str| {
+        obj.lines()
+            .filter_map(|line| line.strip_prefix("v "))
+            .map(|line| {
+                line.split_whitespace()
+                    .nth(2)
+                    .unwrap()
+                    .parse::<f32>()
+                    .unwrap()
+            })
+            .fold(f32::NEG_INFINITY, f32::max)
+    };
+    assert!(max_z(&obj) < 50.0);
+    assert!(max_z(&String::from_utf8(moved_buffer).unwrap()) > 50.0);
+}
+
+#[test]
+fn test_audit_boundary_consistency() {
+    WORLD_LIST
+        .iter()
+        .for_each(|world| assert_eq!(world.audit_boundary_consistency(), Vec::new()));
+    let mut world = WORLD_LIST[1].clone();
+    world
+        .tile_dict
+        .get_mut(&GridCoord::new(0, 0, 0))
+        .unwrap()
+        .fragments
+        .remove(&TileFragment::TriangleZRearRight);
+    assert!(world
+        .audit_boundary_consistency()
+        .contains(&(GridCoord::new(-1, 0, 1), GridCoord::new(0, 0, 0))));
+}
+
+#[cfg(all(feature = "glam-only", feature = "geometric_algebra"))]
+#[test]
+fn test_glam_only_route_targets() {
+    [
+        RouteMotionPrimitive::Plane,
+        RouteMotionPrimitive::PlaneExt,
+        RouteMotionPrimitive::Ladder,
+        RouteMotionPrimitive::LadderExt,
+        RouteMotionPrimitive::Arch,
+        RouteMotionPrimitive::ArchExt,
+        RouteMotionPrimitive::Ramp,
+        RouteMotionPrimitive::RampRiser,
+    ]
+    .iter()
+    .flat_map(|motion_primitive| {
+        [(false, false), (true, false), (false, true), (true, true)]
+            .map(|(backward, flip)| motion_primitive.pivot_motion(backward, flip))
+    })
+    .chain(ROUTE_LIST.iter().map(|route| route.pivotal_motion.clone()))
+    .for_each(|pivotal_motion| {
+        let glam_motion = pivotal_motion.to_motor::<Mat4>();
+        let motor_motion = pivotal_motion.to_motor::<geometric_algebra::ppga3d::Motor>();
+        assert!(glam_motion
+            .target()
+            .abs_diff_eq(motor_motion.target(), 1e-4));
+        let mut glam_trajectory =
+            PivotalMotionTrajectory::from_pivotal_motions(Vec::from([glam_motion]));
+        let mut motor_trajectory =
+            PivotalMotionTrajectory::from_pivotal_motions(Vec::from([motor_motion]));
+        let step = glam_trajectory.total_distance() / 8.0;
+        while let Some(glam_matrix) = glam_trajectory.consume_distance(step) {
+            assert!(glam_matrix.abs_diff_eq(motor_trajectory.consume_distance(step).unwrap(), 1e-4));
+        }
+    });
+}
+
+#[test]
+fn test_replay() {
+    let mut world = WORLD_LIST[0].clone();
+    let initial_world = world.clone();
+    world.start_recording();
+    (0..2).for_each(|_| {
+        let movement_state = world
+            .iter_next_movement_targets()
+            .next()
+            .unwrap()
+            .movement_state;
+        world.motion_to(movement_state).unwrap();
+    });
+    let replay = world.stop_recording();
+    assert_eq!(replay.0.len(), 2);
+
+    let mut replayed_world = initial_world.clone();
+    assert_eq!(replayed_world.play(&replay).count(), 2);
+    assert_eq!(replayed_world.movement_state, world.movement_state);
+}
+
+#[test]
+fn test_tile_centroid() {
+    let world = &WORLD_LIST[0];
+    assert!(world.tile_centroid().unwrap().abs_diff_eq(Vec3::ZERO, 1e-6));
+    assert!(world
+        .projected_tile_centroid()
+        .unwrap()
+        .abs_diff_eq(Vec2::ZERO, 1e-6));
+    let mut empty_world = world.clone();
+    empty_world.tile_dict.clear();
+    assert!(empty_world.tile_centroid().is_none());
+}
+
+#[test]
+fn test_tile_fragment_act() {
+    assert_eq!(
+        TileFragment::TriangleZForeLeft.act(D6::S0),
+        TileFragment::TriangleZRearLeft
+    );
+    assert_eq!(
+        TileFragment::TriangleZForeLeft.act(D6::S3),
+        TileFragment::TriangleZForeRight
+    );
+    assert_eq!(
+        TileFragment::LadderMajorFace.act(D6::R1),
+        TileFragment::LadderMajorFace
+    );
+
+    let triangle_z_fragments = [
+        TileFragment::TriangleZForeLeft,
+        TileFragment::TriangleZForeRight,
+        TileFragment::TriangleZSideLeft,
+        TileFragment::TriangleZSideRight,
+        TileFragment::TriangleZRearLeft,
+        TileFragment::TriangleZRearRight,
+    ];
+    let projected_vertices = |polygons: Polygons| {
+        let mut vertices: Vec<_> = polygons
+            .0
+            .into_iter()
+            .flat_map(|polygon| polygon.vertices)
+            .map(|vertex| {
+                let vertex = Grid::conformal_transform(vertex);
+                (
+                    (vertex.x * 1e3).round() as i32,
+                    (vertex.y * 1e3).round() as i32,
+                )
+            })
+            .collect();
+        vertices.sort();
+        vertices.dedup();
+        vertices
+    };
+    for tile_fragment in triangle_z_fragments {
+        for g in D6::iter_all() {
+            let acted_tile_fragment = tile_fragment.act(g);
+            assert_eq!(
+                projected_vertices(
+                    POLYGONS_DICT[&tile_fragment]
+                        .clone()
+                        .transform(Mat4::from_mat3(Grid::rotation_matrix_from_action(g)))
+                ),
+                projected_vertices(POLYGONS_DICT[&acted_tile_fragment].clone()),
+            );
+        }
+    }
+    TileFragment::iter_all().for_each(|tile_fragment| {
+        D6::iter_all().for_each(|g| {
+            D6::iter_all().for_each(|h| {
+                assert_eq!(tile_fragment.act(g * h), tile_fragment.act(h).act(g));
+            });
+        });
+    });
+}
+
+#[test]
+fn test_trajectory_for_path() {
+    let world = &WORLD_LIST[0];
+    let first_movement_target = world.iter_next_movement_targets().next().unwrap();
+    let second_movement_target = Grid::iter_next_movement_targets_from(
+        first_movement_target.movement_state,
+        &world.tile_dict,
+        &world.route_evaluation_counter,
+    )
+    .next()
+    .unwrap();
+    let path = [
+        first_movement_target.movement_state,
+        second_movement_target.movement_state,
+    ];
+    let mut trajectory = world.trajectory_for_path(&path).unwrap();
+    assert!(
+        (trajectory.total_distance()
+            - PivotalMotionTrajectory::from_pivotal_motions(first_movement_target.pivotal_motions)
+                .total_distance()
+            - PivotalMotionTrajectory::from_pivotal_motions(
+                second_movement_target.pivotal_motions
+            )
+            .total_distance())
+        .abs()
+            < 1e-4
+    );
+    let endpoint = std::iter::from_fn(|| trajectory.consume_distance(1e-3))
+        .last()
+        .unwrap();
+    assert!(endpoint.abs_diff_eq(second_movement_target.transform, 1e-2));
+
+    let mut invalid_path = path;
+    invalid_path.reverse();
+    assert!(world.trajectory_for_path(&invalid_path).is_none());
+    assert_eq!(
+        world.trajectory_for_path(&[]).unwrap().total_distance(),
+        0.0
+    );
+}
+
+#[test]
+fn test_rotate_tile() {
+    let ladder_coord = GridCoord::new(-1, 0, 1);
+    let mut world = WORLD_LIST[1].clone();
+    let anchors: Vec<_> = iter_route_anchors(&world, ladder_coord).collect();
+    world.rotate_tile(ladder_coord, D6::R1).unwrap();
+    assert_eq!(world.tile_dict[&ladder_coord].action, D6::R2);
+    assert_ne!(
+        iter_route_anchors(&world, ladder_coord).collect::<Vec<_>>(),
+        anchors
+    );
+    (1..6).for_each(|_| world.rotate_tile(ladder_coord, D6::R1).unwrap());
+    assert_eq!(
+        iter_route_anchors(&world, ladder_coord).collect::<Vec<_>>(),
+        anchors
+    );
+
+    assert_eq!(
+        world.rotate_tile(GridCoord::new(5, 5, -10), D6::R1),
+        Err(EditError::MissingTile(GridCoord::new(5, 5, -10)))
+    );
+    assert_eq!(
+        world.rotate_tile(GridCoord::new(0, 0, 0), D6::R1),
+        Err(EditError::OccupiedTile(GridCoord::new(0, 0, 0)))
+    );
+    assert_eq!(world.tile_dict[&GridCoord::new(0, 0, 0)].action, D6::R0);
+
+    // A plane triangle cannot be drawn through the ladder, however it is turned.
+    world
+        .tile_dict
+        .get_mut(&ladder_coord)
+        .unwrap()
+        .fragments
+        .insert(TileFragment::TriangleZForeLeft);
+    let action = world.tile_dict[&ladder_coord].action;
+    assert_eq!(
+        world.rotate_tile(ladder_coord, D6::S0),
+        Err(EditError::ConflictingFragments(ladder_coord))
+    );
+    assert_eq!(world.tile_dict[&ladder_coord].action, action);
+}
+
+#[test]
+fn test_frame_style() {
+    fn extent(shapes: &[(Vec<Vec2>, Vec3, [f32; 4])]) -> f32 {
+        shapes
+            .iter()
+            .map(|(shape, _, _)| {
+                let (min, max) = shape.iter().fold(
+                    (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+                    |(min, max), &vertex| (min.min(vertex), max.max(vertex)),
+                );
+                (max - min).min_element()
+            })
+            .fold(0.0, f32::max)
+    }
+
+    let coord = GridCoord::new(0, 0, 0);
+    let mut world = WORLD_LIST[0].clone();
+    let thin_shapes: Vec<_> = world.iter_tile_frame_shapes(coord).collect();
+    assert!(thin_shapes
+        .iter()
+        .all(|(_, _, color)| *color == FrameStyle::default().color));
+    let frame_style = FrameStyle {
+        thickness: 0.2,
+        color: [1.0, 0.5, 0.0, 1.0],
+    };
+    world.set_frame_style(frame_style);
+    let thick_shapes: Vec<_> = world.iter_tile_frame_shapes(coord).collect();
+    assert_eq!(thick_shapes.len(), thin_shapes.len());
+    assert!(thick_shapes
+        .iter()
+        .all(|(_, _, color)| *color == frame_style.color));
+    assert!(extent(&thick_shapes) > extent(&thin_shapes));
+}
+
+#[test]
+fn test_plan_path_weighted() {
+    let world = &WORLD_LIST[0];
+    let goal = MovementState {
+        grid_coord: GridCoord::new(1, -1, 0),
+        ..world.movement_state
+    };
+    let iter_motion_primitives = |path: Vec<MovementState>| {
+        std::iter::once(world.movement_state)
+            .chain(path.clone())
+            .zip(path)
+            .flat_map(|(movement_state, next_movement_state)| {
+                Grid::find_movement_target(
+                    Grid::iter_next_movement_targets_from(
+                        movement_state,
+                        &world.tile_dict,
+                        &world.route_evaluation_counter,
+                    ),
+                    next_movement_state,
+                )
+                .unwrap()
+                .motion_primitives
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let path = world.plan_path_weighted(goal, &HashMap::new()).unwrap();
+    assert_eq!(path.last(), Some(&goal));
+    assert!(iter_motion_primitives(path).contains(&RouteMotionPrimitive::PlaneExt));
+    let detour_path = world
+        .plan_path_weighted(
+            goal,
+            &HashMap::from([(RouteMotionPrimitive::PlaneExt, 10.0)]),
+        )
+        .unwrap();
+    assert_eq!(detour_path.last(), Some(&goal));
+    assert!(iter_motion_primitives(detour_path)
+        .iter()
+        .all(|motion_primitive| *motion_primitive == RouteMotionPrimitive::Plane));
+    assert_eq!(
+        world.plan_path_weighted(world.movement_state, &HashMap::new()),
+        Some(Vec::new())
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_from_json() {
+    let world = &WORLD_LIST[0];
+    let json = serde_json::to_string(&world.to_level()).unwrap();
+    let loaded_world = Grid::from_json(&json).unwrap();
+    assert!(loaded_world.same_level(world));
+    assert!(!loaded_world.same_level(&WORLD_LIST[1]));
+
+    let error = Grid::from_json(&json[..json.len() / 2]).err().unwrap();
+    assert!(matches!(error, LoadError::Json(_)));
+    assert!(error.to_string().starts_with("malformed level json"));
+
+    let mut level_data = world.to_level();
+    level_data.tiles.push(level_data.tiles[0].clone());
+    let error = Grid::from_json(&serde_json::to_string(&level_data).unwrap())
+        .err()
+        .unwrap();
+    assert!(
+        matches!(error, LoadError::Level(LevelError::DuplicateTile(coord)) if coord == level_data.tiles[0].coord)
+    );
+
+    let mut level_data = world.to_level();
+    level_data.movement_state.grid_coord = GridCoord::new(5, 5, -10);
+    let error = Grid::from_json(&serde_json::to_string(&level_data).unwrap())
+        .err()
+        .unwrap();
+    assert!(matches!(
+        error,
+        LoadError::Level(LevelError::MissingStartTile(_))
+    ));
+}
+
+#[test]
+fn test_iter_marker_polygons() {
+    let world = &WORLD_LIST[0];
+    let marker_polygons: Vec<_> = world.iter_marker_polygons().collect();
+    let movement_targets: Vec<_> = world.iter_next_movement_targets().collect();
+    assert_eq!(
+        marker_polygons.len(),
+        movement_targets.len() * MARKER_POLYGONS.0.len()
+    );
+    marker_polygons
+        .chunks(MARKER_POLYGONS.0.len())
+        .zip(&movement_targets)
+        .for_each(|(polygons, movement_target)| {
+            let center =
+                polygons[0].vertices.iter().sum::<Vec3>() / polygons[0].vertices.len() as f32;
+            assert!(center.abs_diff_eq(
+                movement_target
+                    .transform
+                    .transform_point3(Vec3::new(0.0, 0.0, -1.0)),
+                1e-5
+            ));
+        });
+    assert_eq!(world.iter_marker_shapes().count(), marker_polygons.len());
+}
+
+#[test]
+fn test_multiple_players() {
+    let mut world = WORLD_LIST[0].clone();
+    // The demo start transform is only defined up to the viewing direction.
+    let start_transform = world
+        .movement_state_transform(world.movement_state)
+        .unwrap();
+    assert!(
+        Grid::conformal_transform(start_transform.transform_point3(Vec3::ZERO)).abs_diff_eq(
+            Grid::conformal_transform(world.player_transform.transform_point3(Vec3::ZERO)),
+            1e-5
+        )
+    );
+    let co_player_id = world
+        .add_player(MovementState {
+            grid_coord: GridCoord::new(-1, 1, 0),
+            ..world.movement_state
+        })
+        .unwrap();
+    assert_eq!(
+        world.iter_player_ids().collect::<Vec<_>>(),
+        [PlayerId::PRIMARY, co_player_id]
+    );
+    assert_eq!(
+        world.iter_player_shapes(co_player_id).count(),
+        PLAYER_POLYGONS.0.len()
+    );
+
+    let cursor_towards = |world: &Grid, player_id: PlayerId, movement_target: &MovementTarget| {
+        let player_coord = Grid::conformal_transform(
+            world
+                .player(player_id)
+                .unwrap()
+                .transform
+                .transform_point3(Vec3::ZERO),
+        );
+        let target_coord =
+            Grid::conformal_transform(movement_target.transform.transform_point3(Vec3::ZERO));
+        player_coord + (target_coord - player_coord).normalize() * 2.0
+    };
+    let primary_target = world
+        .iter_player_movement_targets(PlayerId::PRIMARY)
+        .next()
+        .unwrap();
+    let co_player_target = world
+        .iter_player_movement_targets(co_player_id)
+        .next()
+        .unwrap();
+    let co_player_cursor = cursor_towards(&world, co_player_id, &co_player_target);
+    assert!(world
+        .player_motion_trajectory(co_player_id, co_player_cursor)
+        .is_some());
+    assert_eq!(
+        world.player_movement_state(co_player_id),
+        Some(co_player_target.movement_state)
+    );
+    assert_eq!(world.movement_state, WORLD_LIST[0].movement_state);
+    let primary_cursor = cursor_towards(&world, PlayerId::PRIMARY, &primary_target);
+    assert!(world
+        .player_motion_trajectory(PlayerId::PRIMARY, primary_cursor)
+        .is_some());
+    assert_eq!(world.movement_state, primary_target.movement_state);
+    assert_eq!(
+        world.player_movement_state(co_player_id),
+        Some(co_player_target.movement_state)
+    );
+
+    let mut world = WORLD_LIST[0].clone();
+    let blocking_target = world.iter_next_movement_targets().next().unwrap();
+    world.add_player(blocking_target.movement_state).unwrap();
+    world.set_player_collision(true);
+    assert!(world.iter_next_movement_targets().all(|movement_target| {
+        movement_target.movement_state.grid_coord != blocking_target.movement_state.grid_coord
+    }));
+    world.set_player_collision(false);
+    assert!(world.iter_next_movement_targets().any(|movement_target| {
+        movement_target.movement_state == blocking_target.movement_state
+    }));
+    assert_eq!(world.player_movement_state(PlayerId(5)), None);
+}
+
+#[test]
+fn test_boundary_fragment() {
+    assert_eq!(
+        WORLD_LIST[0].boundary_fragment(
+            GridCoord::new(0, 0, 0),
+            TileExternalAnchorPosition::ForeLeft
+        ),
+        Some(TileFragment::TriangleZForeLeft)
+    );
+    let world = &WORLD_LIST[1];
+    let ladder_coord = GridCoord::new(-1, 0, 1);
+    [
+        TileExternalAnchorPosition::ForeLeft,
+        TileExternalAnchorPosition::RearRight,
+    ]
+    .into_iter()
+    .for_each(|position| {
+        assert_eq!(
+            world.boundary_fragment(ladder_coord, position),
+            Some(TileFragment::LadderMajorFace)
+        );
+        assert!(world
+            .tile_dict
+            .contains_key(&ladder_coord.add_offset(position.into_offset())));
+    });
+    assert_eq!(
+        world.boundary_fragment(ladder_coord, TileExternalAnchorPosition::SideLeft),
+        None
+    );
+    assert_eq!(
+        world.boundary_fragment(
+            GridCoord::new(5, 5, -10),
+            TileExternalAnchorPosition::SideLeft
+        ),
+        None
+    );
+}
+
+#[test]
+fn test_play_bounds() {
+    let mut world = WORLD_LIST[0].clone();
+    let outside_coord = GridCoord::new(0, 1, -1);
+    let inside_coord = GridCoord::new(1, 0, -1);
+    let iter_target_coords = |world: &Grid| -> Vec<_> {
+        world
+            .iter_next_movement_targets()
+            .map(|movement_target| movement_target.movement_state.grid_coord)
+            .collect()
+    };
+    assert!(iter_target_coords(&world).contains(&outside_coord));
+    world.set_play_bounds(Some((GridCoord::new(0, -1, -1), GridCoord::new(1, 0, 0))));
+    assert!(world.collides_with_bounds(outside_coord));
+    assert!(!world.collides_with_bounds(inside_coord));
+    let target_coords = iter_target_coords(&world);
+    assert!(!target_coords.contains(&outside_coord));
+    assert!(target_coords.contains(&inside_coord));
+    assert!(target_coords.contains(&GridCoord::new(0, 0, 0)));
+    world.set_play_bounds(None);
+    assert!(iter_target_coords(&world).contains(&outside_coord));
+}
+
+#[test]
+fn test_rotate_level() {
+    fn iter_move_edges(world: &Grid) -> HashSet<(MovementState, MovementState)> {
+        world
+            .tile_dict
+            .iter()
+            .flat_map(|(&coord, tile)| {
+                ROUTE_LIST
+                    .iter()
+                    .filter(|route| route.fragments_requirement.is_subset(&tile.fragments))
+                    .filter(|route| route.initial_anchor.stationery)
+                    .map(move |route| MovementState {
+                        grid_coord: coord,
+                        anchor: route.initial_anchor.act(tile.action),
+                    })
+            })
+            .flat_map(|movement_state| {
+                Grid::iter_next_movement_targets_from(
+                    movement_state,
+                    &world.tile_dict,
+                    &world.route_evaluation_counter,
+                )
+                .map(move |movement_target| (movement_state, movement_target.movement_state))
+            })
+            .collect()
+    }
+
+    let world = &WORLD_LIST[1];
+    let rotated_world = world.rotate_level(D6::R2);
+    assert_eq!(rotated_world.tile_dict.len(), world.tile_dict.len());
+    assert_eq!(
+        rotated_world.movement_state,
+        world.movement_state.act(D6::R2)
+    );
+    let edges = iter_move_edges(world);
+    assert!(!edges.is_empty());
+    assert_eq!(
+        iter_move_edges(&rotated_world),
+        edges
+            .into_iter()
+            .map(|(initial, terminal)| (initial.act(D6::R2), terminal.act(D6::R2)))
+            .collect()
+    );
+    // The coordinate action moves neighbors the way anchor positions do.
+    D6::iter_all().for_each(|action| {
+        [
+            TileExternalAnchorPosition::ForeLeft,
+            TileExternalAnchorPosition::SideRight,
+            TileExternalAnchorPosition::RearLeft,
+        ]
+        .into_iter()
+        .for_each(|position| {
+            assert_eq!(
+                GridCoord(position.into_offset()).act(action),
+                GridCoord(position.act(action).into_offset())
+            );
+        });
+    });
+}
+
+#[test]
+fn test_is_solid() {
+    let world = &WORLD_LIST[1];
+    let empty_coord = GridCoord::new(0, 1, -1);
+    assert!(!world.is_occupied(empty_coord));
+    assert!(!world.is_solid(empty_coord));
+    let ladder_coord = GridCoord::new(-1, 0, 1);
+    assert!(world.is_occupied(ladder_coord));
+    assert!(world.is_solid(ladder_coord));
+    let triangle_coord = GridCoord::new(0, 0, 0);
+    assert!(world.is_occupied(triangle_coord));
+    assert!(!world.is_solid(triangle_coord));
+    // Triangles next to an arch face do not make the arch tile any less solid.
+    assert!(world.is_solid(GridCoord::new(0, -3, 3)));
+}
+
+#[test]
+fn test_reachability_diff() {
+    let mut world = WORLD_LIST[1].clone();
+    world.movement_state.anchor.position_axis =
+        TileAnchorPositionAxis::Internal(TileInternalAnchorPositionAxis::PlaneRearZ);
+    let (only_world, only_same_world) = world.reachability_diff(&world);
+    assert!(only_world.is_empty() && only_same_world.is_empty());
+    let mut other = world.clone();
+    other.rotate_tile(GridCoord::new(-1, 0, 1), D6::S4).unwrap();
+    // Reorienting the ladder cuts off everything past it, and nothing new
+    // becomes reachable.
+    let (only_world, only_other) = world.reachability_diff(&other);
+    assert!(only_other.is_empty());
+    let cut_off_coords: HashSet<_> = [
+        GridCoord::new(-1, 0, 1),
+        GridCoord::new(-2, 0, 2),
+        GridCoord::new(-1, -1, 2),
+        GridCoord::new(-1, -2, 3),
+        GridCoord::new(0, -3, 3),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(
+        only_world,
+        world
+            .reachable_states()
+            .into_iter()
+            .filter(|movement_state| cut_off_coords.contains(&movement_state.grid_coord))
+            .collect()
+    );
+    assert!(only_world.contains(&MovementState {
+        grid_coord: GridCoord::new(-2, 0, 2),
+        anchor: TileAnchor {
+            position_axis: TileAnchorPositionAxis::Internal(
+                TileInternalAnchorPositionAxis::PlaneForeZ
+            ),
+            sign: TileAnchorSign::Neg,
+            stationery: true,
+        },
+    }));
+    assert!(!only_world.contains(&world.movement_state));
+}
+
+#[test]
+fn test_preview_with_tile() {
+    let coord = GridCoord::new(1, 0, -1);
+    let mut world = WORLD_LIST[0].clone();
+    let tile = world.tile_dict.remove(&coord).unwrap();
+    let iter_next_movement_states = |world: &Grid| -> HashSet<_> {
+        world
+            .iter_next_movement_targets()
+            .map(|movement_target| movement_target.movement_state)
+            .collect()
+    };
+    let movement_states = iter_next_movement_states(&world);
+    let preview: HashSet<_> = world
+        .preview_with_tile(coord, tile.fragments.iter().copied(), tile.action)
+        .into_iter()
+        .collect();
+    assert_ne!(preview, movement_states);
+    assert!(!world.is_occupied(coord));
+    assert_eq!(iter_next_movement_states(&world), movement_states);
+    world.tile_dict.insert(coord, tile);
+    assert_eq!(preview, iter_next_movement_states(&world));
+}
+
+#[test]
+fn test_next_hint() {
+    let mut world = WORLD_LIST[0].clone();
+    assert_eq!(world.next_hint(), None);
+    let goal = MovementState {
+        grid_coord: GridCoord::new(1, -1, 0),
+        ..world.movement_state
+    };
+    world.set_goal(Some(goal));
+    let path = world.plan_path_weighted(goal, &HashMap::new()).unwrap();
+    let hint = world.next_hint().unwrap();
+    assert_eq!(path.first(), Some(&hint));
+    assert!(world
+        .iter_next_movement_targets()
+        .any(|movement_target| movement_target.movement_state == hint));
+    world.movement_state = hint;
+    assert_eq!(
+        world
+            .plan_path_weighted(goal, &HashMap::new())
+            .unwrap()
+            .len(),
+        path.len() - 1
+    );
+    world.movement_state = goal;
+    assert_eq!(world.next_hint(), None);
+}
+
+#[test]
+fn test_tile_transform() {
+    fn assert_shifted(shapes: Vec<Vec<Vec2>>, original_shapes: &[Vec<Vec2>], shift: Vec2) {
+        assert_eq!(shapes.len(), original_shapes.len());
+        shapes
+            .iter()
+            .flatten()
+            .zip(original_shapes.iter().flatten())
+            .for_each(|(point, original_point)| {
+                assert!(point.abs_diff_eq(*original_point + shift, 1e-4))
+            });
+    }
+
+    let coord = GridCoord::new(1, 0, -1);
+    let mut world = WORLD_LIST[0].clone();
+    let iter_fragment_shapes = |world: &Grid, coord| -> Vec<_> {
+        world
+            .iter_tile_fragment_shapes(coord)
+            .map(|(shape, _)| shape)
+            .collect()
+    };
+    let iter_frame_shapes = |world: &Grid| -> Vec<_> {
+        world
+            .iter_tile_frame_shapes(coord)
+            .map(|(shape, _, _)| shape)
+            .collect()
+    };
+    let iter_next_movement_states = |world: &Grid| -> Vec<_> {
+        world
+            .iter_next_movement_targets()
+            .map(|movement_target| movement_target.movement_state)
+            .collect()
+    };
+    let fragment_shapes = iter_fragment_shapes(&world, coord);
+    let frame_shapes = iter_frame_shapes(&world);
+    let origin_fragment_shapes = iter_fragment_shapes(&world, GridCoord::new(0, 0, 0));
+    let movement_states = iter_next_movement_states(&world);
+
+    let offset = Vec3::new(0.0, 0.0, 3.0);
+    let shift = Grid::conformal_transform(offset);
+    world.set_tile_transform(coord, Mat4::from_translation(offset));
+    assert_shifted(iter_fragment_shapes(&world, coord), &fragment_shapes, shift);
+    assert_shifted(iter_frame_shapes(&world), &frame_shapes, shift);
+    assert_eq!(
+        iter_fragment_shapes(&world, GridCoord::new(0, 0, 0)),
+        origin_fragment_shapes
+    );
+    assert_eq!(iter_next_movement_states(&world), movement_states);
+    world.clear_tile_transform(coord);
+    assert_shifted(
+        iter_fragment_shapes(&world, coord),
+        &fragment_shapes,
+        Vec2::ZERO,
+    );
+}
+
+#[test]
+fn test_solid_mesh() {
+    let world = &WORLD_LIST[1];
+    let (positions, indices) = world.solid_mesh();
+    assert!(!positions.is_empty());
+    assert_eq!(indices.len() % 3, 0);
+    assert!(indices
+        .iter()
+        .all(|&index| (index as usize) < positions.len()));
+    // Every triangle lies within the cube of some solid tile, as turned by the
+    // tile action.
+    let solid_cubes: Vec<_> = world
+        .iter_coords()
+        .filter(|&coord| world.is_solid(coord))
+        .map(|coord| {
+            (
+                coord.grid_position(),
+                Grid::rotation_matrix_from_action(world.tile_dict[&coord].action).transpose(),
+            )
+        })
+        .collect();
+    assert!(indices.chunks(3).all(|triangle| {
+        solid_cubes.iter().any(|&(center, inverse_matrix)| {
+            triangle.iter().all(|&index| {
+                (inverse_matrix * (positions[index as usize] - center))
+                    .abs()
+                    .cmple(Vec3::splat(1.0 + 1e-5))
+                    .all()
+            })
+        })
+    }));
+    assert_eq!(WORLD_LIST[0].solid_mesh(), (Vec::new(), Vec::new()));
+
+    // Watertight: every edge is shared by exactly two triangles.
+    let mut world = WORLD_LIST[1].clone();
+    world.tile_dict.insert(
+        GridCoord::new(3, -3, 0),
+        Tile {
+            fragments: HashSet::from([TileFragment::RampFace, TileFragment::RampBulkSide]),
+            action: D6::S1,
+        },
+    );
+    let (_, indices) = world.solid_mesh();
+    let mut edge_counts: HashMap<(u32, u32), usize> = HashMap::new();
+    indices.chunks(3).for_each(|triangle| {
+        (0..3).for_each(|index| {
+            let (start, end) = (triangle[index], triangle[(index + 1) % 3]);
+            *edge_counts
+                .entry((start.min(end), start.max(end)))
+                .or_default() += 1;
+        });
+    });
+    assert!(!edge_counts.is_empty());
+    assert!(edge_counts.values().all(|&count| count == 2));
+}
+
+#[test]
+fn test_restart_from_checkpoint() {
+    let mut world = WORLD_LIST[0].clone();
+    assert!(world.restart_from_checkpoint().is_none());
+    let iter_next_movement_target =
+        |world: &Grid| world.iter_next_movement_targets().next().unwrap();
+    let movement_target = iter_next_movement_target(&world);
+    world.motion_to(movement_target.movement_state).unwrap();
+    world.set_player_transform(movement_target.transform);
+    assert_eq!(world.move_count(), 1);
+    world.set_checkpoint();
+    let checkpoint_movement_state = world.movement_state;
+    let movement_target = iter_next_movement_target(&world);
+    world.motion_to(movement_target.movement_state).unwrap();
+    world.set_player_transform(movement_target.transform);
+    assert_eq!(world.move_count(), 2);
+    assert_ne!(world.movement_state, checkpoint_movement_state);
+    let trajectory = world.restart_from_checkpoint().unwrap();
+    assert_eq!(trajectory.total_distance(), 0.0);
+    assert_eq!(world.movement_state, checkpoint_movement_state);
+    assert_eq!(world.move_count(), 1);
+    assert_eq!(
+        world.player_transform,
+        world.checkpoint.as_ref().unwrap().player_transform
+    );
+}
+
+#[test]
+fn test_recording_follows_history() {
+    let mut world = WORLD_LIST[0].clone();
+    let iter_next_movement_state = |world: &Grid| {
+        world
+            .iter_next_movement_targets()
+            .next()
+            .unwrap()
+            .movement_state
+    };
+    world.start_recording();
+    let movement_state = iter_next_movement_state(&world);
+    world.motion_to(movement_state).unwrap();
+    world.set_checkpoint();
+    world.motion_to(iter_next_movement_state(&world)).unwrap();
+    world.motion_to(iter_next_movement_state(&world)).unwrap();
+    world.undo().unwrap();
+    world.restart_from_checkpoint().unwrap();
+    assert_eq!(world.move_history(), [movement_state]);
+    let replay = world.stop_recording();
+    assert_eq!(replay, world.export_history());
+
+    world.start_recording();
+    world.motion_to(iter_next_movement_state(&world)).unwrap();
+    world.undo().unwrap();
+    assert_eq!(world.stop_recording(), Replay::default());
+}
+
+#[test]
+fn test_cycle_tile_action() {
+    let mut world = WORLD_LIST[1].clone();
+    let coord = GridCoord::new(-1, 0, 1);
+    let action = world.tile_dict[&coord].action;
+    let action_orbit = Grid::action_orbit(action);
+    assert_eq!(action_orbit.len(), 6);
+    assert_eq!(action_orbit.iter().collect::<HashSet<_>>().len(), 6);
+    let actions: Vec<_> = (0..6)
+        .map(|_| world.cycle_tile_action(coord).unwrap())
+        .collect();
+    assert_eq!(actions.last(), Some(&action));
+    assert_eq!(
+        std::iter::once(action)
+            .chain(actions.into_iter().take(5))
+            .collect::<Vec<_>>(),
+        action_orbit
+    );
+    assert_eq!(
+        world.cycle_tile_action(GridCoord::new(0, 0, 0)),
+        Err(EditError::OccupiedTile(GridCoord::new(0, 0, 0)))
+    );
+}
+
+#[cfg(feature = "compact")]
+#[test]
+fn test_level_bytes() {
+    WORLD_LIST.iter().for_each(|world| {
+        let bytes = world.to_bytes();
+        assert_eq!(bytes[..4], LEVEL_BYTES_MAGIC);
+        assert!(world.same_level(&Grid::from_bytes(&bytes).unwrap()));
+    });
+    let mut world = WORLD_LIST[1].clone();
+    world.movement_state = MovementState {
+        grid_coord: GridCoord::new(-2, 0, 2),
+        anchor: TileAnchor {
+            position_axis: TileAnchorPositionAxis::External(
+                TileExternalAnchorPosition::SideLeft,
+                TileExternalAnchorAxis::Z,
+            ),
+            sign: TileAnchorSign::Neg,
+            stationery: true,
         },
-        Grid {
-            tile_dict: map_macro::hash_map! {
-                GridCoord::new(0, 0, 0) => Tile {
-                    fragments: map_macro::hash_set! {
-                        TileFragment::TriangleZSideLeft,
-                        TileFragment::TriangleZRearLeft,
-                        TileFragment::TriangleZRearRight,
-                    },
-                    action: D6::R0,
-                },
-                GridCoord::new(-1, 0, 1) => Tile {
-                    fragments: map_macro::hash_set! {
-                        TileFragment::LadderMajorFace
-                    },
-                    action: D6::R1,
-                },
-                GridCoord::new(-2, 0, 2) => Tile {
-                    fragments: map_macro::hash_set! {
-                        TileFragment::TriangleZForeLeft,
-                        TileFragment::TriangleZForeRight,
-                        TileFragment::TriangleZSideLeft,
-                    },
-                    action: D6::R0,
-                },
-                GridCoord::new(-1, -1, 2) => Tile {
-                    fragments: map_macro::hash_set! {
-                        TileFragment::TriangleZSideRight,
-                        TileFragment::TriangleZRearLeft,
-                        TileFragment::TriangleZRearRight,
-                    },
-                    action: D6::R0,
-                },
-                GridCoord::new(-1, -2, 3) => Tile {
-                    fragments: map_macro::hash_set! {
-                        TileFragment::TriangleZForeLeft,
-                        TileFragment::TriangleZForeRight,
-                        TileFragment::TriangleZSideLeft,
-                    },
-                    action: D6::R0,
-                },
-                GridCoord::new(0, -3, 3) => Tile {
-                    fragments: map_macro::hash_set! {
-                        TileFragment::TriangleXRear,
-                        TileFragment::TriangleYFore,
-                        TileFragment::ArchMinorFace,
-                    },
-                    action: D6::R2,
-                },
-                GridCoord::new(1, -3, 2) => Tile {
-                    fragments: map_macro::hash_set! {
-                        TileFragment::TriangleZSideRight,
-                        TileFragment::TriangleZRearLeft,
-                        TileFragment::TriangleZRearRight,
-                    },
-                    action: D6::R5,
-                },
-                GridCoord::new(1, -2, 1) => Tile {
-                    fragments: map_macro::hash_set! {
-                        TileFragment::ArchMajorFace,
-                    },
-                    action: D6::R2,
-                },
-                GridCoord::new(1, -1, 0) => Tile {
-                    fragments: map_macro::hash_set! {
-                        TileFragment::TriangleZSideRight,
-                        TileFragment::TriangleZRearLeft,
-                        TileFragment::TriangleZRearRight,
-                    },
-                    action: D6::R0,
-                },
-            },
-            movement_state: MovementState {
-                grid_coord: GridCoord::new(0, 0, 0),
-                anchor: TileAnchor {
-                    position_axis: TileAnchorPositionAxis::Internal(
-                        TileInternalAnchorPositionAxis::PlaneForeZ,
-                    ),
-                    sign: TileAnchorSign::Pos,
-                    stationery: true,
-                },
-            },
-            player_transform: Mat4::from_translation(Vec3::new(1.0, 1.0, 0.0)),
+    };
+    assert!(world.same_level(&Grid::from_bytes(&world.to_bytes()).unwrap()));
+
+    let mut bytes = world.to_bytes();
+    bytes[4] = LEVEL_BYTES_VERSION + 1;
+    let error = Grid::from_bytes(&bytes).err().unwrap();
+    assert!(
+        matches!(error, LoadError::UnsupportedVersion(version) if version == LEVEL_BYTES_VERSION + 1)
+    );
+    assert!(error.to_string().contains("version"));
+    assert!(matches!(
+        Grid::from_bytes(b"D6L").err().unwrap(),
+        LoadError::Truncated
+    ));
+    assert!(matches!(
+        Grid::from_bytes(&world.to_bytes()[..20]).err().unwrap(),
+        LoadError::Bincode(_)
+    ));
+    assert!(matches!(
+        Grid::from_bytes(b"JSON!").err().unwrap(),
+        LoadError::InvalidHeader
+    ));
+}
+
+#[test]
+fn test_route_kind() {
+    assert!(WORLD_LIST[0]
+        .iter_next_movement_targets()
+        .all(|movement_target| movement_target.route_kind() == RouteKind::Plane));
+    let mut world = WORLD_LIST[1].clone();
+    world.movement_state.anchor.position_axis =
+        TileAnchorPositionAxis::Internal(TileInternalAnchorPositionAxis::PlaneRearZ);
+    let (ladder_targets, plane_targets): (Vec<_>, Vec<_>) = world
+        .iter_next_movement_targets()
+        .partition(|movement_target| {
+            movement_target.movement_state().grid_coord == GridCoord::new(-1, 0, 1)
+        });
+    assert_eq!(ladder_targets.len(), 1);
+    assert_eq!(ladder_targets[0].route_kind(), RouteKind::Ladder);
+    assert_eq!(
+        ladder_targets[0].motion_primitives(),
+        [RouteMotionPrimitive::Plane, RouteMotionPrimitive::Ladder]
+    );
+    assert!(!plane_targets.is_empty());
+    assert!(plane_targets
+        .iter()
+        .all(|movement_target| movement_target.route_kind() == RouteKind::Plane));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_from_level() {
+    let world = &WORLD_LIST[1];
+    let json = serde_json::to_string(&world.to_level()).unwrap();
+    let level_data: LevelData = serde_json::from_str(&json).unwrap();
+    assert_eq!(level_data, world.to_level());
+    let loaded_world = Grid::from_level(level_data).unwrap();
+    assert_eq!(loaded_world.tile_dict, world.tile_dict);
+    assert_eq!(loaded_world.to_level(), world.to_level());
+    let tile = &world.tile_dict[&GridCoord::new(0, 0, 0)];
+    assert_eq!(
+        serde_json::from_str::<Tile>(&serde_json::to_string(tile).unwrap()).unwrap(),
+        *tile
+    );
+
+    let mut level_data = world.to_level();
+    let fragment = level_data.tiles[0].fragments[0];
+    level_data.tiles[0].fragments.push(fragment);
+    let coord = level_data.tiles[0].coord;
+    let error = Grid::from_level(level_data).err().unwrap();
+    assert_eq!(error, LevelError::DuplicateFragment(coord, fragment));
+    assert!(error.to_string().contains(&format!("{fragment:?}")));
+}
+
+#[test]
+fn test_difficulty_estimate() {
+    let mut simple_world = WORLD_LIST[0].clone();
+    simple_world.set_goal(Some(MovementState {
+        grid_coord: GridCoord::new(1, -1, 0),
+        ..simple_world.movement_state
+    }));
+    let mut long_world = WORLD_LIST[1].clone();
+    long_world.movement_state.anchor.position_axis =
+        TileAnchorPositionAxis::Internal(TileInternalAnchorPositionAxis::PlaneRearZ);
+    long_world.set_goal(Some(MovementState {
+        grid_coord: GridCoord::new(0, -3, 3),
+        anchor: TileAnchor {
+            position_axis: TileAnchorPositionAxis::Internal(
+                TileInternalAnchorPositionAxis::ArchMinorFaceXY,
+            ),
+            sign: TileAnchorSign::Pos,
+            stationery: true,
         },
-    ];
+    }));
+    let simple_difficulty = simple_world.difficulty_estimate();
+    let long_difficulty = long_world.difficulty_estimate();
+    assert!(simple_difficulty.is_finite() && long_difficulty.is_finite());
+    assert!(simple_difficulty < long_difficulty);
+    long_world.set_goal(None);
+    assert!(long_world.difficulty_estimate() < long_difficulty);
+    long_world.set_goal(Some(MovementState {
+        grid_coord: GridCoord::new(9, 0, -9),
+        ..long_world.movement_state
+    }));
+    assert_eq!(long_world.difficulty_estimate(), f32::INFINITY);
 }
 
 #[test]
-fn test() {
+fn test_grid_builder() {
     let world = &WORLD_LIST[0];
-    world
+    let builder = world
+        .tile_dict
+        .iter()
+        .fold(GridBuilder::new(), |builder, (&coord, tile)| {
+            builder.insert_tile(coord, tile.fragments.clone(), tile.action)
+        });
+    assert_eq!(
+        builder.clone().build().err(),
+        Some(BuildError::MissingStart)
+    );
+    let built_world = builder
+        .clone()
+        .start(GridCoord::new(0, 0, 0), StartAnchor::PlaneForeZ)
+        .build()
+        .unwrap();
+    assert_eq!(built_world.tile_dict, world.tile_dict);
+    assert_eq!(built_world.movement_state, world.movement_state);
+    // The pose is derived from the routes, which place the player a multiple of
+    // (1, 1, 1) away from the hand-written demo pose.
+    assert!(
+        Grid::conformal_transform(built_world.player_transform.transform_point3(Vec3::ZERO))
+            .abs_diff_eq(
+                Grid::conformal_transform(world.player_transform.transform_point3(Vec3::ZERO)),
+                1e-5
+            )
+    );
+    assert_eq!(
+        built_world
+            .iter_next_movement_targets()
+            .map(|movement_target| movement_target.movement_state)
+            .collect::<Vec<_>>(),
+        world
+            .iter_next_movement_targets()
+            .map(|movement_target| movement_target.movement_state)
+            .collect::<Vec<_>>()
+    );
+
+    assert_eq!(
+        GridBuilder::new()
+            .start(GridCoord::new(0, 0, 0), StartAnchor::PlaneForeZ)
+            .build()
+            .err(),
+        Some(BuildError::EmptyLevel)
+    );
+    assert_eq!(
+        builder
+            .clone()
+            .start(GridCoord::new(4, 0, -4), StartAnchor::PlaneForeZ)
+            .build()
+            .err(),
+        Some(BuildError::MissingStartTile(GridCoord::new(4, 0, -4)))
+    );
+    assert_eq!(
+        builder
+            .insert_tile(GridCoord::new(4, 0, -4), HashSet::new(), D6::R0)
+            .start(GridCoord::new(4, 0, -4), StartAnchor::PlaneRearZ)
+            .build()
+            .err(),
+        Some(BuildError::UnsupportedStart(GridCoord::new(4, 0, -4)))
+    );
+}
+
+#[test]
+fn test_reachable_states() {
+    let world = &WORLD_LIST[0];
+    let reachable_states = world.reachable_states();
+    assert!(!reachable_states.is_empty());
+    assert_eq!(world.reachable_states(), reachable_states);
+    assert!(reachable_states.contains(&world.movement_state.canonical()));
+    assert!(reachable_states.iter().all(|movement_state| movement_state
+        .synonym()
+        .is_none_or(|synonym| !reachable_states.contains(&synonym) || synonym == *movement_state)));
+}
+
+#[test]
+fn test_connecting_action_delta() {
+    let coord = GridCoord::new(-1, 0, 1);
+    let neighbor_coord = GridCoord::new(0, 0, 0);
+    let mut world = WORLD_LIST[1].clone();
+    assert_eq!(
+        world.connecting_action_delta(coord, neighbor_coord),
+        Some(D6::R0)
+    );
+    assert_eq!(
+        world.connecting_action_delta(coord, GridCoord::new(4, 0, -4)),
+        None
+    );
+    let neighbor_tile = world.tile_dict.get_mut(&neighbor_coord).unwrap();
+    neighbor_tile.action = D6::R1 * neighbor_tile.action;
+    let action_delta = world
+        .connecting_action_delta(coord, neighbor_coord)
+        .unwrap();
+    assert_ne!(action_delta, D6::R0);
+    let neighbor_tile = world.tile_dict.get_mut(&neighbor_coord).unwrap();
+    neighbor_tile.action = action_delta * neighbor_tile.action;
+    assert_eq!(
+        world.connecting_action_delta(coord, neighbor_coord),
+        Some(D6::R0)
+    );
+}
+
+#[test]
+fn test_path_to() {
+    let world = &WORLD_LIST[1];
+    assert_eq!(world.path_to(world.movement_state).unwrap().len(), 0);
+    assert!(world
+        .path_to(MovementState {
+            grid_coord: GridCoord::new(-1, 0, 1),
+            ..world.movement_state
+        })
+        .is_none());
+
+    let mut world = WORLD_LIST[1].clone();
+    world.movement_state.anchor.position_axis =
+        TileAnchorPositionAxis::Internal(TileInternalAnchorPositionAxis::PlaneRearZ);
+    let target = world
+        .reachable_states()
+        .into_iter()
+        .find(|movement_state| movement_state.grid_coord == GridCoord::new(-1, -2, 3))
+        .unwrap();
+    let path = world.plan_path_weighted(target, &HashMap::new()).unwrap();
+    let movement_targets = world.movement_targets_for_path(&path).unwrap();
+    assert!(movement_targets.len() > 1);
+    assert_eq!(
+        movement_targets.last().unwrap().movement_state.canonical(),
+        target
+    );
+    assert!(movement_targets
+        .iter()
+        .any(|movement_target| movement_target.route_kind() != RouteKind::Plane));
+    movement_targets
+        .iter()
+        .fold(world.movement_state, |movement_state, movement_target| {
+            assert!(Grid::iter_next_movement_targets_from(
+                movement_state,
+                &world.tile_dict,
+                &world.route_evaluation_counter,
+            )
+            .any(|next_movement_target| next_movement_target.movement_state
+                == movement_target.movement_state));
+            movement_target.movement_state
+        });
+    let pivotal_motions = world.path_to(target).unwrap();
+    assert_eq!(
+        pivotal_motions.len(),
+        movement_targets
+            .iter()
+            .map(|movement_target| movement_target.pivotal_motions.len())
+            .sum::<usize>()
+    );
+    let goal_transform = movement_targets.last().unwrap().transform;
+    assert!(pivotal_motions
+        .last()
+        .unwrap()
+        .target()
+        .abs_diff_eq(goal_transform, 1e-4));
+    let mut trajectory = PivotalMotionTrajectory::from_pivotal_motions(pivotal_motions);
+    assert!(trajectory.total_distance() > 0.0);
+    assert!(trajectory
+        .consume_distance(trajectory.total_distance())
+        .unwrap()
+        .abs_diff_eq(goal_transform, 1e-4));
+}
+
+#[test]
+fn test_player_coord() {
+    WORLD_LIST.iter().for_each(|world| {
+        assert_eq!(world.player_coord(), world.movement_state().grid_coord());
+    });
+    let mut world = WORLD_LIST[1].clone();
+    world.movement_state.anchor.position_axis =
+        TileAnchorPositionAxis::Internal(TileInternalAnchorPositionAxis::PlaneRearZ);
+    world.player_transform = world
+        .movement_state_transform(world.movement_state)
+        .unwrap();
+    let movement_state = world.movement_state();
+    let movement_target = world
         .iter_next_movement_targets()
-        // .skip(0)
-        // .next()
-        .for_each(|movement_target| {
-            dbg!(movement_target.clone());
-            let mut pivotal_motion_path =
-                PivotalMotionTrajectory::from_pivotal_motions(movement_target.pivotal_motions);
-            while let Some(transform) = pivotal_motion_path.consume_distance(0.4) {
-                dbg!(transform.transform_point3(Vec3::ZERO));
+        .find(|movement_target| movement_target.route_kind() == RouteKind::Ladder)
+        .unwrap();
+    let transform = movement_target.transform;
+    world.commit_movement_target(movement_target);
+    world.set_player_transform(transform);
+    assert_ne!(world.movement_state(), movement_state);
+    assert_eq!(world.player_coord(), world.movement_state().grid_coord());
+    assert_ne!(
+        world.movement_state().anchor_key(),
+        movement_state.anchor_key()
+    );
+    assert_eq!(
+        MovementState {
+            grid_coord: GridCoord::new(-1, 0, 1),
+            ..movement_state
+        }
+        .anchor_key()
+        .1,
+        movement_state.anchor_key().1
+    );
+}
+
+#[test]
+fn test_move_history() {
+    let mut world = WORLD_LIST[0].clone();
+    for _ in 0..4 {
+        let movement_state = world
+            .iter_next_movement_targets()
+            .map(|movement_target| movement_target.movement_state)
+            .max_by_key(|movement_state| movement_state.grid_coord.0.to_array())
+            .unwrap();
+        world.motion_to(movement_state).unwrap();
+    }
+    assert_eq!(world.move_history().len(), world.move_count());
+    assert_eq!(world.move_history().last(), Some(&world.movement_state));
+    let replay = world.export_history();
+    let mut replayed_world = WORLD_LIST[0].clone();
+    assert_eq!(replayed_world.play(&replay).count(), 4);
+    assert_eq!(replayed_world.movement_state, world.movement_state);
+    assert_eq!(replayed_world.move_history(), world.move_history());
+}
+
+#[test]
+fn test_snapshot() {
+    let mut world = WORLD_LIST[0].clone();
+    let snapshot = world.snapshot();
+    let movement_state = world
+        .iter_next_movement_targets()
+        .next()
+        .unwrap()
+        .movement_state;
+    world.motion_to(movement_state).unwrap();
+    world.set_player_transform(Mat4::from_translation(Vec3::X));
+    assert_ne!(world.snapshot(), snapshot);
+    assert_eq!(world.restore(&snapshot), Ok(()));
+    assert_eq!(world.movement_state, WORLD_LIST[0].movement_state);
+    assert_eq!(world.player_transform, WORLD_LIST[0].player_transform);
+
+    let invalid_snapshot = GridSnapshot {
+        movement_state: MovementState {
+            grid_coord: GridCoord::new(4, 0, -4),
+            ..snapshot.movement_state
+        },
+        ..snapshot.clone()
+    };
+    assert_eq!(
+        world.restore(&invalid_snapshot),
+        Err(RestoreError::MissingTile(GridCoord::new(4, 0, -4)))
+    );
+    assert_eq!(world.snapshot(), snapshot);
+    assert_eq!(
+        world.restore(&GridSnapshot {
+            move_history: Vec::from([movement_state]),
+            ..snapshot.clone()
+        }),
+        Err(RestoreError::HistoryMismatch)
+    );
+}
+
+#[test]
+fn test_undo_after_restore() {
+    let mut world = WORLD_LIST[0].clone();
+    let movement_state = world
+        .iter_next_movement_targets()
+        .next()
+        .unwrap()
+        .movement_state;
+    world.motion_to(movement_state).unwrap();
+    let snapshot = world.snapshot();
+    let next_movement_state = world
+        .iter_next_movement_targets()
+        .next()
+        .unwrap()
+        .movement_state;
+    world.motion_to(next_movement_state).unwrap();
+    world.undo().unwrap();
+    assert_eq!(world.restore(&snapshot), Ok(()));
+    assert_eq!(world.move_count(), 1);
+    assert_eq!(world.move_history().last(), Some(&world.movement_state));
+    assert!(world.undo().is_none());
+    assert!(world.redo().is_none());
+    assert_eq!(world.movement_state, movement_state);
+    assert_eq!(world.export_history(), Replay(Vec::from([movement_state])));
+
+    let mut fresh_world = WORLD_LIST[0].clone();
+    assert_eq!(fresh_world.restore(&snapshot), Ok(()));
+    assert_eq!(fresh_world.move_history(), world.move_history());
+}
+
+#[test]
+fn test_redo() {
+    fn sample_poses(mut trajectory: PivotalMotionTrajectory) -> Vec<Mat4> {
+        let step = trajectory.total_distance() / 12.0;
+        std::iter::once(trajectory.peek().unwrap())
+            .chain((0..12).map(|_| trajectory.consume_distance_tracked(step).0.unwrap()))
+            .collect()
+    }
+
+    let mut world = WORLD_LIST[0].clone();
+    assert!(world.redo().is_none());
+    let movement_states: Vec<_> = world
+        .iter_next_movement_targets()
+        .map(|movement_target| movement_target.movement_state)
+        .collect();
+    let (movement_state, other_movement_state) = (movement_states[0], movement_states[1]);
+    let trajectory = world.motion_to(movement_state).unwrap();
+    let undo_trajectory = world.undo().unwrap();
+    assert_eq!(world.movement_state, WORLD_LIST[0].movement_state);
+    assert_eq!(world.move_count(), 0);
+    let poses = sample_poses(trajectory.clone());
+    let mut undo_poses = sample_poses(undo_trajectory);
+    undo_poses.reverse();
+    assert_eq!(poses.len(), undo_poses.len());
+    poses
+        .iter()
+        .zip(&undo_poses)
+        .for_each(|(pose, undo_pose)| assert!(pose.abs_diff_eq(*undo_pose, 1e-4)));
+    let redo_trajectory = world.redo().unwrap();
+    assert_eq!(world.movement_state, movement_state);
+    assert_eq!(world.move_history(), [movement_state]);
+    assert_eq!(sample_poses(redo_trajectory), poses);
+    assert!(world.redo().is_none());
+
+    world.undo().unwrap();
+    world.motion_to(other_movement_state).unwrap();
+    assert!(world.redo().is_none());
+    assert_eq!(world.movement_state, other_movement_state);
+}
+
+#[test]
+fn test_history_limit() {
+    let mut world = WORLD_LIST[0].clone();
+    world.set_history_limit(2);
+    let movement_states: Vec<_> = (0..3)
+        .map(|_| {
+            let movement_state = world
+                .iter_next_movement_targets()
+                .map(|movement_target| movement_target.movement_state)
+                .max_by_key(|movement_state| movement_state.grid_coord.0.to_array())
+                .unwrap();
+            world.motion_to(movement_state).unwrap();
+            movement_state
+        })
+        .collect();
+    assert!(world.undo().is_some());
+    assert_eq!(world.movement_state, movement_states[1]);
+    assert!(world.undo().is_some());
+    assert_eq!(world.movement_state, movement_states[0]);
+    assert!(world.undo().is_none());
+    assert!(world.redo().is_some());
+    assert!(world.redo().is_some());
+    assert_eq!(world.movement_state, movement_states[2]);
+
+    world.set_history_limit(0);
+    assert!(world.undo().is_none());
+}
+
+#[test]
+fn test_tiles_in_draw_order() {
+    let world = &WORLD_LIST[1];
+    let coords = world.tiles_in_draw_order();
+    assert_eq!(coords.len(), world.tile_dict.len());
+    assert_eq!(world.tiles_in_draw_order(), coords);
+    assert!(coords.windows(2).all(|window| {
+        Grid::conformal_transform(window[0].grid_position()).y
+            <= Grid::conformal_transform(window[1].grid_position()).y
+    }));
+    // Projected heights only depend on z here, and equal heights are ordered
+    // by coordinates.
+    assert_eq!(coords.first(), Some(&GridCoord::new(0, 0, 0)));
+    assert_eq!(coords.last(), Some(&GridCoord::new(0, -3, 3)));
+}
+
+#[test]
+fn test_iter_tile_fragment_shapes_filtered() {
+    let coord = GridCoord::new(-1, 0, 1);
+    let mut world = WORLD_LIST[1].clone();
+    world
+        .tile_dict
+        .get_mut(&coord)
+        .unwrap()
+        .fragments
+        .insert(TileFragment::TriangleZForeLeft);
+    let tile = &world.tile_dict[&coord];
+    let shape_count = |tile_fragment| {
+        let mut polygons = Polygons::default();
+        Grid::tile_fragment_polygons_into(
+            coord,
+            tile,
+            tile_fragment,
+            Mat4::IDENTITY,
+            &mut polygons,
+        );
+        polygons.0.len()
+    };
+    assert_eq!(
+        world
+            .iter_tile_fragment_shapes_filtered(coord, |tile_fragment| {
+                tile_fragment == TileFragment::LadderMajorFace
+            })
+            .count(),
+        shape_count(TileFragment::LadderMajorFace)
+    );
+    assert_eq!(
+        world
+            .iter_tile_fragment_shapes_filtered(coord, |tile_fragment| {
+                tile_fragment != TileFragment::LadderMajorFace
+            })
+            .count(),
+        shape_count(TileFragment::TriangleZForeLeft)
+    );
+    assert!(shape_count(TileFragment::TriangleZForeLeft) > 0);
+    assert_eq!(
+        world
+            .iter_tile_fragment_shapes_filtered(coord, |_| false)
+            .count(),
+        0
+    );
+    assert_eq!(
+        world.iter_tile_fragment_shapes(coord).count(),
+        shape_count(TileFragment::LadderMajorFace) + shape_count(TileFragment::TriangleZForeLeft)
+    );
+}
+
+#[test]
+fn test_movement_graph_dot() {
+    let world = &WORLD_LIST[0];
+    let dot = world.movement_graph_dot();
+    assert!(dot.starts_with("digraph movement_graph {"));
+    assert!(dot.trim_end().ends_with('}'));
+    assert_eq!(dot.matches('{').count(), dot.matches('}').count());
+    assert_eq!(
+        dot.lines().filter(|line| line.contains("[label=")).count(),
+        world.reachable_states().len()
+    );
+    assert!(dot
+        .lines()
+        .any(|line| line.contains("[0, 0, 0] Internal(PlaneForeZ) Pos")
+            && line.contains("peripheries=2")));
+    assert!(dot.lines().any(|line| line.contains(" -> ")));
+}
+
+#[test]
+fn test_route_index() {
+    let anchors: HashSet<_> = ROUTE_LIST
+        .iter()
+        .flat_map(|route| [route.initial_anchor, route.terminal_anchor])
+        .flat_map(|anchor| D6::iter_all().map(move |action| anchor.act(action)))
+        .collect();
+    anchors.into_iter().for_each(|anchor| {
+        D6::iter_all().for_each(|action| {
+            let indexed_routes: Vec<_> = Grid::iter_candidate_routes(anchor, action)
+                .map(|route| route as *const Route)
+                .collect();
+            let scanned_routes: Vec<_> = ROUTE_LIST
+                .iter()
+                .filter(|route| route.initial_anchor.act(action) == anchor)
+                .map(|route| route as *const Route)
+                .collect();
+            assert_eq!(indexed_routes, scanned_routes);
+        });
+    });
+    assert_eq!(
+        ROUTE_INDEX.values().map(Vec::len).sum::<usize>(),
+        ROUTE_LIST.len()
+    );
+}
+
+#[test]
+fn test_cached_targets() {
+    let mut world = WORLD_LIST[0].clone();
+    let marker_shapes: Vec<_> = world.iter_marker_shapes().collect();
+    assert_eq!(
+        world.iter_marker_shapes().collect::<Vec<_>>(),
+        marker_shapes
+    );
+    let cursor_coord = Grid::conformal_transform(
+        world
+            .cached_targets()
+            .first()
+            .unwrap()
+            .transform
+            .transform_point3(Vec3::ZERO),
+    );
+    assert!(world.motion_trajectory(cursor_coord).is_some());
+    assert_ne!(
+        world.iter_marker_shapes().collect::<Vec<_>>(),
+        marker_shapes
+    );
+    world.set_play_bounds(Some((GridCoord::new(5, 5, 5), GridCoord::new(5, 5, 5))));
+    assert_eq!(world.iter_marker_shapes().count(), 0);
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+fn test_target_computations() {
+    let mut world = WORLD_LIST[0].clone();
+    assert_eq!(world.target_computations(), 0);
+    world.iter_marker_shapes().for_each(drop);
+    world.iter_marker_shapes().for_each(drop);
+    assert_eq!(world.target_computations(), 1);
+    let cursor_coord = Grid::conformal_transform(
+        world
+            .cached_targets()
+            .first()
+            .unwrap()
+            .transform
+            .transform_point3(Vec3::ZERO),
+    );
+    assert!(world.motion_trajectory(cursor_coord).is_some());
+    assert_eq!(world.target_computations(), 1);
+    world.iter_marker_shapes().for_each(drop);
+    assert_eq!(world.target_computations(), 2);
+    world.set_play_bounds(Some((GridCoord::new(5, 5, 5), GridCoord::new(5, 5, 5))));
+    world.iter_marker_shapes().for_each(drop);
+    assert_eq!(world.target_computations(), 3);
+}
+
+#[test]
+fn test_motion_config() {
+    let mut world = WORLD_LIST[0].clone();
+    let movement_target = world.iter_next_movement_targets().next().unwrap();
+    let target_coord = movement_target.movement_state.grid_coord;
+    world.set_play_bounds(Some((target_coord, target_coord)));
+    let player_coord =
+        Grid::conformal_transform(world.player_transform.transform_point3(Vec3::ZERO));
+    let target_direction =
+        Grid::conformal_transform(movement_target.transform.transform_point3(Vec3::ZERO))
+            - player_coord;
+    let cursor_coord = player_coord
+        + Vec2::from_angle(std::f32::consts::FRAC_PI_4).rotate(target_direction.normalize()) * 2.0;
+    assert!(world.pending_movement_target(cursor_coord).is_none());
+    world.set_motion_config(MotionConfig {
+        angle_threshold: std::f32::consts::FRAC_PI_3,
+        ..MotionConfig::default()
+    });
+    assert_eq!(
+        world
+            .pending_movement_target(cursor_coord)
+            .map(|movement_target| movement_target.movement_state),
+        Some(movement_target.movement_state)
+    );
+    world.set_motion_config(MotionConfig {
+        radius_threshold: 3.0,
+        angle_threshold: std::f32::consts::FRAC_PI_3,
+    });
+    assert!(world.pending_movement_target(cursor_coord).is_none());
+}
+
+#[test]
+fn test_inverse_conformal_transform() {
+    let mut world = WORLD_LIST[0].clone();
+    let vectors = [
+        Vec3::ZERO,
+        Vec3::new(1.0, -2.0, 3.0),
+        Vec3::new(-0.5, 0.25, 4.0),
+        GridCoord::new(-1, -2, 3).grid_position(),
+    ];
+    vectors.into_iter().for_each(|vector| {
+        let depth = Grid::conformal_depth(vector);
+        assert!(world
+            .inverse_conformal_transform(Grid::conformal_transform(vector), depth)
+            .abs_diff_eq(vector, 1e-5));
+    });
+    assert!(world
+        .inverse_conformal_transform(Vec2::ZERO, 3.0_f32.sqrt())
+        .abs_diff_eq(Vec3::ONE, 1e-5));
+    assert!(Grid::conformal_transform(
+        world.inverse_conformal_transform(Vec2::new(0.5, -1.5), -2.0)
+    )
+    .abs_diff_eq(Vec2::new(0.5, -1.5), 1e-5));
+
+    // Picking goes through the rotated view as well.
+    world.rotate_view(D6::R1).unwrap();
+    vectors.into_iter().for_each(|vector| {
+        assert!(world
+            .inverse_conformal_transform(world.view_transform(vector), world.view_depth(vector))
+            .abs_diff_eq(vector, 1e-5));
+    });
+    let movement_target = world.iter_next_movement_targets().next().unwrap();
+    let target_position = movement_target.transform.transform_point3(Vec3::ZERO);
+    let cursor_coord = world.view_transform(target_position);
+    assert!(world
+        .inverse_conformal_transform(cursor_coord, world.view_depth(target_position))
+        .abs_diff_eq(target_position, 1e-5));
+    let target_coord = movement_target.movement_state.grid_coord;
+    world.set_play_bounds(Some((target_coord, target_coord)));
+    assert_eq!(
+        world
+            .pending_movement_target(cursor_coord)
+            .map(|movement_target| movement_target.movement_state),
+        Some(movement_target.movement_state)
+    );
+}
+
+#[test]
+fn test_iter_all_shapes_sorted() {
+    let world = &WORLD_LIST[0];
+    let sorted_polygons = world.sorted_polygons();
+    assert!(sorted_polygons.windows(2).all(|window| {
+        let [depth_0, depth_1] =
+            [&window[0].0, &window[1].0].map(|polygon| Grid::conformal_depth(polygon.centroid()));
+        depth_0 <= depth_1
+    }));
+    let shapes = world.iter_all_shapes_sorted();
+    let kind_count = |shape_kind| {
+        shapes
+            .iter()
+            .filter(|(_, _, kind)| *kind == shape_kind)
+            .count()
+    };
+    assert_eq!(
+        kind_count(ShapeKind::Fragment),
+        world.iter_all_fragment_shapes().count()
+    );
+    assert_eq!(
+        kind_count(ShapeKind::Frame),
+        world
+            .iter_coords()
+            .flat_map(|coord| world.iter_tile_frame_shapes(coord))
+            .count()
+    );
+    assert_eq!(
+        kind_count(ShapeKind::Player),
+        world.iter_player_shapes(PlayerId::PRIMARY).count()
+    );
+    assert_eq!(
+        kind_count(ShapeKind::Marker),
+        world.iter_marker_shapes().count()
+    );
+    assert_eq!(world.clone().iter_all_shapes_sorted(), shapes);
+}
+
+#[test]
+fn test_iter_tile_fragment_shapes_visible() {
+    let world = &WORLD_LIST[0];
+    assert_eq!(
+        world.iter_all_fragment_shapes_visible().count(),
+        world.iter_all_fragment_shapes().count()
+    );
+    let coord = GridCoord::new(0, 0, 0);
+    let mut world = world.clone();
+    world.tile_dict.insert(
+        coord,
+        Tile {
+            fragments: HashSet::from([TileFragment::ArchMinorFace]),
+            action: D6::R0,
+        },
+    );
+    let shape_count = world.iter_tile_fragment_shapes(coord).count();
+    let visible_shapes: Vec<_> = world.iter_tile_fragment_shapes_visible(coord).collect();
+    assert_eq!(visible_shapes.len() * 2, shape_count);
+    assert!(visible_shapes
+        .iter()
+        .all(|(_, normal)| normal.dot(VIEW_DIRECTION) > 0.0));
+}
+
+#[test]
+fn test_to_obj() {
+    let world = &WORLD_LIST[0];
+    let obj = world.to_obj();
+    let count = |prefix: &str| obj.lines().filter(|line| line.starts_with(prefix)).count();
+    let frame_polygon_count = frame_polygons(world.frame_style.thickness).0.len();
+    let polygons: Vec<_> = world
+        .iter_coords()
+        .flat_map(|coord| {
+            let mut polygons = Polygons::default();
+            world.tile_polygons_into(coord, &mut polygons);
+            polygons.0
+        })
+        .chain(PLAYER_POLYGONS.0.iter().cloned())
+        .collect();
+    let face_count = polygons.len() + world.iter_coords().count() * frame_polygon_count;
+    let vertex_count = polygons
+        .iter()
+        .map(|polygon| polygon.vertices.len())
+        .sum::<usize>()
+        + world.iter_coords().count()
+            * frame_polygons(world.frame_style.thickness)
+                .0
+                .iter()
+                .map(|polygon| polygon.vertices.len())
+                .sum::<usize>();
+    assert_eq!(count("o tile_"), world.iter_coords().count());
+    assert_eq!(count("f "), face_count);
+    assert_eq!(count("vn "), face_count);
+    assert_eq!(count("v "), vertex_count);
+    assert!(obj
+        .lines()
+        .filter(|line| line.starts_with("f "))
+        .all(|line| {
+            line.split_whitespace().skip(1).all(|index| {
+                index
+                    .split("//")
+                    .next()
+                    .and_then(|index| index.parse::<usize>().ok())
+                    .is_some_and(|index| (1..=vertex_count).contains(&index))
+            })
+        }));
+}
+
+#[test]
+fn test_ramp_route() {
+    let mut world = WORLD_LIST[0].clone();
+    let plane_tile = world.tile_dict[&GridCoord::new(0, 0, 0)].clone();
+    let (ramp_coord, far_coord) = (GridCoord::new(0, 2, -2), GridCoord::new(0, 3, -3));
+    world.tile_dict.insert(far_coord, plane_tile);
+    assert!(world
+        .reachable_states()
+        .iter()
+        .all(|movement_state| movement_state.grid_coord != far_coord));
+    world.tile_dict.insert(
+        ramp_coord,
+        Tile {
+            fragments: HashSet::from([
+                TileFragment::RampFace,
+                TileFragment::RampBulkSide,
+                TileFragment::RampCompSide,
+            ]),
+            action: D6::R2,
+        },
+    );
+    let reachable_states = world.reachable_states();
+    let ramp_state = *reachable_states
+        .iter()
+        .find(|movement_state| {
+            movement_state.grid_coord == ramp_coord
+                && movement_state.anchor.position_axis
+                    == TileAnchorPositionAxis::Internal(TileInternalAnchorPositionAxis::RampFace)
+        })
+        .unwrap();
+    // The ramp leads on to the tile across it, not only back down.
+    assert!(reachable_states
+        .iter()
+        .any(|movement_state| movement_state.grid_coord == far_coord));
+    world.movement_state = ramp_state;
+    let ramp_targets: Vec<_> = world.iter_next_movement_targets().collect();
+    assert!(ramp_targets
+        .iter()
+        .all(|movement_target| movement_target.motion_primitives[0].kind() == RouteKind::Ramp));
+    assert!(ramp_targets
+        .iter()
+        .any(|movement_target| movement_target.movement_state.grid_coord == far_coord));
+    assert!(ramp_targets
+        .iter()
+        .any(|movement_target| movement_target.movement_state.grid_coord != far_coord));
+
+    // Both routes meet halfway up the slope, and run along the face.
+    let coords = super::fragment::FragmentCoords::Ramp.coords(1);
+    let on_face = |position: Vec3| {
+        let coord = -position.truncate();
+        position.z.abs() < 1e-5
+            && coords.windows(2).any(|window| {
+                let (start, end) = (window[0], window[1]);
+                let t = ((coord - start).dot(end - start) / (end - start).length_squared())
+                    .clamp(0.0, 1.0);
+                coord.distance(start + t * (end - start)) < 1e-5
+            })
+    };
+    ROUTE_LIST
+        .iter()
+        .filter(|route| route.motion_primitive.kind() == RouteKind::Ramp)
+        .for_each(|route| {
+            assert!((0..=8).all(|i| on_face(
+                route
+                    .pivotal_motion
+                    .eval(i as f32 / 8.0)
+                    .transform_point3(Vec3::ZERO)
+            )));
+            let internal_position = if matches!(
+                route.terminal_anchor.position_axis,
+                TileAnchorPositionAxis::Internal(_)
+            ) {
+                route.pivotal_motion.target()
+            } else {
+                route.pivotal_motion.eval(0.0)
             }
+            .transform_point3(Vec3::ZERO);
+            assert!(internal_position.abs_diff_eq(
+                Vec3::new(0.0, std::f32::consts::FRAC_PI_6.tan() - 1.0, 0.0),
+                1e-5
+            ));
         });
 }
+
+#[test]
+fn test_validate() {
+    WORLD_LIST
+        .iter()
+        .for_each(|world| assert_eq!(world.validate(), Ok(())));
+    let mut world = WORLD_LIST[1].clone();
+    world
+        .tile_dict
+        .get_mut(&GridCoord::new(-1, 0, 1))
+        .unwrap()
+        .fragments
+        .extend([
+            TileFragment::TriangleZForeLeft,
+            TileFragment::TriangleZForeRight,
+        ]);
+    assert_eq!(
+        world.validate(),
+        Err(Vec::from([
+            ValidationError::IncompatibleFragments(
+                GridCoord::new(-1, 0, 1),
+                TileFragment::TriangleZForeLeft,
+                TileFragment::LadderMajorFace,
+            ),
+            ValidationError::IncompatibleFragments(
+                GridCoord::new(-1, 0, 1),
+                TileFragment::TriangleZForeRight,
+                TileFragment::LadderMajorFace,
+            ),
+        ]))
+    );
+}
+
+#[test]
+fn test_is_solved() {
+    let mut world = WORLD_LIST[1].clone();
+    world.movement_state.anchor.position_axis =
+        TileAnchorPositionAxis::Internal(TileInternalAnchorPositionAxis::PlaneRearZ);
+    assert!(!world.is_solved());
+    assert!(!world.is_solvable());
+    let goal = world
+        .reachable_states()
+        .into_iter()
+        .find(|movement_state| movement_state.grid_coord == GridCoord::new(-1, -2, 3))
+        .unwrap();
+    world.set_goal(Some(goal));
+    assert!(world.is_solvable());
+    let path = world.plan_path_weighted(goal, &HashMap::new()).unwrap();
+    for movement_target in world.movement_targets_for_path(&path).unwrap() {
+        assert!(!world.is_solved());
+        world.commit_movement_target(movement_target);
+    }
+    assert!(world.is_solved());
+
+    world.set_goal(Some(MovementState {
+        grid_coord: GridCoord::new(5, 5, -10),
+        ..goal
+    }));
+    assert!(!world.is_solved());
+    assert!(!world.is_solvable());
+}
+
+#[test]
+fn test_iter_marker_previews() {
+    let mut world = WORLD_LIST[1].clone();
+    world.movement_state.anchor.position_axis =
+        TileAnchorPositionAxis::Internal(TileInternalAnchorPositionAxis::PlaneRearZ);
+    let movement_targets: Vec<_> = world.iter_next_movement_targets().collect();
+    let previews: Vec<_> = world.iter_marker_previews(0.25).collect();
+    assert_eq!(previews.len(), movement_targets.len());
+    previews.iter().zip(&movement_targets).for_each(
+        |((movement_state, transforms), movement_target)| {
+            assert_eq!(*movement_state, movement_target.movement_state);
+            let total_distance = PivotalMotionTrajectory::from_pivotal_motions(
+                movement_target.pivotal_motions.clone(),
+            )
+            .total_distance();
+            assert_eq!(
+                transforms.len(),
+                (total_distance / 0.25 - 1e-4).ceil() as usize + 1
+            );
+            assert!(transforms
+                .last()
+                .unwrap()
+                .abs_diff_eq(movement_target.transform, 1e-4));
+        },
+    );
+    assert!(world
+        .iter_marker_previews(0.1)
+        .zip(&previews)
+        .all(|((_, fine_transforms), (_, transforms))| fine_transforms.len() > transforms.len()));
+    assert_eq!(world.iter_marker_previews(0.0).count(), 0);
+    assert_eq!(world.iter_marker_previews(-1.0).count(), 0);
+    assert_eq!(world.iter_marker_previews(f32::NAN).count(), 0);
+}
+
+#[test]
+fn test_neighbors() {
+    let world = &WORLD_LIST[1];
+    assert_eq!(
+        GridCoord::new(0, 0, 0)
+            .iter_neighbor_coords()
+            .collect::<Vec<_>>(),
+        [
+            GridCoord::new(1, 0, -1),
+            GridCoord::new(0, 1, -1),
+            GridCoord::new(1, -1, 0),
+            GridCoord::new(-1, 1, 0),
+            GridCoord::new(0, -1, 1),
+            GridCoord::new(-1, 0, 1),
+        ]
+    );
+    assert_eq!(
+        world.neighbors(GridCoord::new(0, 0, 0)),
+        [GridCoord::new(1, -1, 0), GridCoord::new(-1, 0, 1)]
+    );
+    assert!(world.neighbors(GridCoord::new(5, 5, -10)).is_empty());
+    assert!(world.iter_coords().all(|coord| {
+        world
+            .neighbors(coord)
+            .iter()
+            .all(|&neighbor_coord| world.neighbors(neighbor_coord).contains(&coord))
+    }));
+}
+
+#[test]
+fn test_tile_polygons_into() {
+    let mut world = WORLD_LIST[1].clone();
+    world.set_tile_transform(
+        GridCoord::new(0, -3, 3),
+        Mat4::from_translation(Vec3::new(0.5, 0.0, -1.0)),
+    );
+    let mut out = Polygons(Vec::new());
+    let mut coords: Vec<_> = world.iter_coords().collect();
+    coords.sort_by_key(|coord| coord.0.to_array());
+    coords.into_iter().for_each(|coord| {
+        world.tile_polygons_into(coord, &mut out);
+        let tile = &world.tile_dict[&coord];
+        let expected_polygons: Vec<_> = tile
+            .fragments
+            .iter()
+            .flat_map(|&tile_fragment| {
+                let mut polygons = Polygons::default();
+                Grid::tile_fragment_polygons_into(
+                    coord,
+                    tile,
+                    tile_fragment,
+                    world.tile_transform(coord),
+                    &mut polygons,
+                );
+                polygons.0
+            })
+            .collect();
+        assert_eq!(out.0.len(), expected_polygons.len());
+        out.0
+            .iter()
+            .zip(&expected_polygons)
+            .for_each(|(polygon, expected_polygon)| {
+                assert_eq!(polygon.vertices, expected_polygon.vertices);
+                assert_eq!(polygon.normal, expected_polygon.normal);
+            });
+    });
+    // A buffer left over from another tile gives the same shapes as a fresh one.
+    assert_eq!(
+        world
+            .iter_all_fragment_shapes_with_scratch(&mut out)
+            .collect::<Vec<_>>(),
+        world.iter_all_fragment_shapes().collect::<Vec<_>>()
+    );
+    world.tile_polygons_into(GridCoord::new(5, 5, -10), &mut out);
+    assert!(out.0.is_empty());
+}
+
+#[test]
+fn test_iter_reachable_coord_shapes() {
+    let world = &WORLD_LIST[0];
+    let coords: HashSet<_> = world
+        .iter_next_movement_targets()
+        .map(|movement_target| movement_target.movement_state.canonical().grid_coord)
+        .filter(|&coord| coord != world.movement_state.grid_coord)
+        .collect();
+    assert!(!coords.is_empty());
+    let shapes: Vec<_> = world.iter_reachable_coord_shapes().collect();
+    assert_eq!(
+        shapes.len(),
+        coords.len() * frame_polygons(world.frame_style.thickness).0.len()
+    );
+    let current_frame_shapes: Vec<_> = world
+        .iter_tile_frame_shapes(world.movement_state.grid_coord)
+        .map(|(shape, _, _)| shape)
+        .collect();
+    assert!(shapes
+        .iter()
+        .all(|(shape, _)| !current_frame_shapes.contains(shape)));
+    assert!(coords.iter().all(|&coord| {
+        world.iter_tile_frame_shapes(coord).all(|(shape, _, _)| {
+            shapes
+                .iter()
+                .any(|(reachable_shape, _)| *reachable_shape == shape)
+        })
+    }));
+}
+
+#[test]
+fn test_try_from_offset() {
+    EXTERNAL_ANCHOR_POSITIONS.into_iter().for_each(|position| {
+        assert_eq!(
+            TileExternalAnchorPosition::try_from_offset(position.into_offset()),
+            Some(position)
+        );
+    });
+    assert_eq!(
+        TileExternalAnchorPosition::try_from_offset(I16Vec3::new(1, 0, -1)),
+        Some(TileExternalAnchorPosition::ForeLeft)
+    );
+    [
+        I16Vec3::new(2, 0, -2),
+        I16Vec3::ZERO,
+        I16Vec3::new(1, 1, 1),
+        I16Vec3::new(1, 0, 0),
+    ]
+    .into_iter()
+    .for_each(|offset| assert_eq!(TileExternalAnchorPosition::try_from_offset(offset), None));
+}
+
+#[test]
+fn test_view_action() {
+    let mut world = WORLD_LIST[0].clone();
+    let player_position = world.player_transform.transform_point3(Vec3::ZERO);
+    assert_eq!(world.view_action(), D6::R0);
+    assert_eq!(
+        world.view_transform(player_position),
+        Grid::conformal_transform(player_position)
+    );
+    let player_shapes: Vec<_> = world.iter_player_shapes(PlayerId::PRIMARY).collect();
+    let all_shapes = world.iter_all_shapes_sorted();
+    world.rotate_view(D6::R1).unwrap();
+    assert_eq!(world.view_action(), D6::R1);
+    assert!(!world
+        .view_transform(player_position)
+        .abs_diff_eq(Grid::conformal_transform(player_position), 1e-5));
+    let movement_target = world.iter_next_movement_targets().next().unwrap();
+    let target_coord = movement_target.movement_state.grid_coord;
+    world.set_play_bounds(Some((target_coord, target_coord)));
+    let cursor_coord = world.view_transform(movement_target.transform.transform_point3(Vec3::ZERO));
+    assert_eq!(
+        world
+            .pending_movement_target(cursor_coord)
+            .map(|movement_target| movement_target.movement_state),
+        Some(movement_target.movement_state)
+    );
+    world.set_play_bounds(None);
+    // Normals turn with the view, so the facing test still sees (1, 1, 1).
+    assert!(world
+        .iter_all_fragment_shapes_visible()
+        .all(|(_, normal)| normal.dot(VIEW_DIRECTION) > 0.0));
+    assert_eq!(
+        world.rotate_view(D6::S0),
+        Err(ViewError::UpsideDown(D6::S0))
+    );
+    assert_eq!(
+        world.set_view_action(D6::S3),
+        Err(ViewError::UpsideDown(D6::S3))
+    );
+    assert_eq!(world.view_action(), D6::R1);
+    world.rotate_view(D6::R1.inverse()).unwrap();
+    assert_eq!(world.view_action(), D6::R0);
+    assert_eq!(
+        world
+            .iter_player_shapes(PlayerId::PRIMARY)
+            .collect::<Vec<_>>(),
+        player_shapes
+    );
+    assert_eq!(world.iter_all_shapes_sorted(), all_shapes);
+}
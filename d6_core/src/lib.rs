@@ -1,5 +1,6 @@
 pub mod d6;
 pub mod fragment;
 pub mod grid;
+mod metrics;
 pub mod pga;
 pub mod polygon;
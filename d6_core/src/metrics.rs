@@ -0,0 +1,27 @@
+// Counts work done on behalf of the `metrics` feature. Compiles down to a
+// no-op unless the feature is enabled, and clones start again from zero.
+#[derive(Debug, Default)]
+pub(crate) struct Counter(#[cfg(feature = "metrics")] std::sync::atomic::AtomicUsize);
+
+impl Counter {
+    pub(crate) fn reset(&self) {
+        #[cfg(feature = "metrics")]
+        self.0.store(0, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    pub(crate) fn increment(&self) {
+        #[cfg(feature = "metrics")]
+        self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    #[cfg(feature = "metrics")]
+    pub(crate) fn get(&self) -> usize {
+        self.0.load(std::sync::atomic::Ordering::Relaxed)
+    }
+}
+
+impl Clone for Counter {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
@@ -1,26 +1,143 @@
-use geometric_algebra::ppga3d::Line;
-use geometric_algebra::ppga3d::Motor;
-use geometric_algebra::ppga3d::Point;
-use geometric_algebra::Exp;
-use geometric_algebra::GeometricProduct;
-use geometric_algebra::Magnitude;
-use geometric_algebra::RegressiveProduct;
-use geometric_algebra::Signum;
-use geometric_algebra::Transformation;
 use glam::Mat3;
 use glam::Mat4;
 use glam::Quat;
 use glam::Vec3;
 
-// PGA4CS, section 6.7 Example: Univresal Motors, pp.62-64
-// https://enkimute.github.io/ganja.js/examples/coffeeshop.html#chapter11_motors
+use super::metrics::Counter;
+
+// Magnitudes below this are treated as zero when making geometric decisions.
+pub const GEOMETRY_EPSILON: f32 = 1e-6;
+
+// The representation pivots are exponentiated into and composed in. PGA motors
+// are the default; the `glam-only` feature, or leaving out the
+// `geometric_algebra` feature, switches to plain matrices.
+#[cfg(all(feature = "geometric_algebra", not(feature = "glam-only")))]
+pub type DefaultMotor = geometric_algebra::ppga3d::Motor;
+#[cfg(not(all(feature = "geometric_algebra", not(feature = "glam-only"))))]
+pub type DefaultMotor = Mat4;
+
+pub trait RigidMotor: Copy + std::fmt::Debug {
+    fn identity() -> Self;
+
+    fn from_pivot(pivot: Pivot) -> Self;
+
+    // Only rigid matrices are supported.
+    fn from_mat4(matrix: Mat4) -> Self;
+
+    // Applies `other` first, then `self`.
+    fn compose(self, other: Self) -> Self;
+
+    fn to_mat4(self) -> Mat4;
+}
+
+impl RigidMotor for Mat4 {
+    fn identity() -> Self {
+        Mat4::IDENTITY
+    }
+
+    // Evaluates the exponential of the line directly as a screw motion: a
+    // rotation by `|d|` about the axis through `(d x m) / |d|^2`, composed with a
+    // translation by the component of `m` parallel to `d`.
+    fn from_pivot(pivot: Pivot) -> Self {
+        let Pivot { moment, direction } = pivot;
+        let angle = direction.length();
+        if angle <= GEOMETRY_EPSILON {
+            return Mat4::from_translation(moment);
+        }
+        let parallel_moment = direction * direction.dot(moment) / (angle * angle);
+        let axis_point = direction.cross(moment - parallel_moment) / (angle * angle);
+        Mat4::from_translation(parallel_moment + axis_point)
+            * Mat4::from_axis_angle(direction / angle, angle)
+            * Mat4::from_translation(-axis_point)
+    }
+
+    fn from_mat4(matrix: Mat4) -> Self {
+        matrix
+    }
+
+    fn compose(self, other: Self) -> Self {
+        self * other
+    }
+
+    fn to_mat4(self) -> Mat4 {
+        self
+    }
+}
+
+#[cfg(feature = "geometric_algebra")]
+mod ppga {
+    use geometric_algebra::ppga3d::Line;
+    use geometric_algebra::ppga3d::Motor;
+    use geometric_algebra::ppga3d::Point;
+    use geometric_algebra::Exp;
+    use geometric_algebra::GeometricProduct;
+    use geometric_algebra::Signum;
+    use geometric_algebra::Transformation;
+    use glam::Mat3;
+    use glam::Mat4;
+
+    use super::Pivot;
+    use super::RigidMotor;
+
+    // PGA4CS, section 6.7 Example: Univresal Motors, pp.62-64
+    // https://enkimute.github.io/ganja.js/examples/coffeeshop.html#chapter11_motors
+    impl RigidMotor for Motor {
+        fn identity() -> Self {
+            Self::from_pivot(Pivot::zero())
+        }
+
+        fn from_pivot(pivot: Pivot) -> Self {
+            let Pivot { moment, direction } = pivot;
+            (Line::new(
+                moment.x,
+                moment.y,
+                moment.z,
+                direction.x,
+                direction.y,
+                direction.z,
+            ) * (-1.0 / 2.0))
+                .exp()
+        }
+
+        fn from_mat4(matrix: Mat4) -> Self {
+            Self::from_pivot(Pivot::from_translation_vector(matrix.w_axis.truncate())).compose(
+                Self::from_pivot(Pivot::from_rotation_matrix(Mat3::from_mat4(matrix))),
+            )
+        }
+
+        fn compose(self, other: Self) -> Self {
+            self.geometric_product(other)
+        }
+
+        fn to_mat4(self) -> Mat4 {
+            let x_axis = self.transformation(Point::new(0.0, 1.0, 0.0, 0.0));
+            let y_axis = self.transformation(Point::new(0.0, 0.0, 1.0, 0.0));
+            let z_axis = self.transformation(Point::new(0.0, 0.0, 0.0, 1.0));
+            let w_axis = self.transformation(Point::new(1.0, 0.0, 0.0, 0.0)).signum();
+            Mat4::from_cols_array_2d(&[
+                [x_axis[1], x_axis[2], x_axis[3], x_axis[0]],
+                [y_axis[1], y_axis[2], y_axis[3], y_axis[0]],
+                [z_axis[1], z_axis[2], z_axis[3], z_axis[0]],
+                [w_axis[1], w_axis[2], w_axis[3], w_axis[0]],
+            ])
+        }
+    }
+}
+
+// A line in Plucker coordinates, exponentiated into a rigid motion.
 #[derive(Clone, Copy, Debug)]
-pub struct Pivot(Line);
+pub struct Pivot {
+    moment: Vec3,
+    direction: Vec3,
+}
 
 impl Pivot {
     // Plucker coordinates convention: (q - p : p cross q) <=> line from p to q
     pub fn from_plucker(d: Vec3, m: Vec3) -> Self {
-        Self(Line::new(m.x, m.y, m.z, d.x, d.y, d.z))
+        Self {
+            moment: m,
+            direction: d,
+        }
     }
 
     pub fn from_rotation_matrix(matrix: Mat3) -> Self {
@@ -35,52 +152,162 @@ impl Pivot {
         Self::from_plucker(Vec3::ZERO, Vec3::ZERO)
     }
 
-    fn as_motor(&self) -> Motor {
-        (self.0 * (-1.0 / 2.0)).exp()
+    pub fn to_mat4(&self) -> Mat4 {
+        DefaultMotor::from_pivot(*self).to_mat4()
     }
 
-    fn distance(&self, point: Point) -> f32 {
-        point.regressive_product(self.0).magnitude()
+    // The inverse of `to_screw` for nonzero angles: a rotation by `angle` about
+    // the axis through `axis_point` along `axis_direction`, together with a
+    // translation by `pitch * angle` along the axis.
+    pub fn from_screw(axis_point: Vec3, axis_direction: Vec3, angle: f32, pitch: f32) -> Self {
+        let axis_direction = axis_direction.normalize();
+        let direction = angle * axis_direction;
+        Self::from_plucker(
+            direction,
+            axis_point.cross(direction) + pitch * angle * axis_direction,
+        )
+    }
+
+    // Decomposes the pivot into `(axis_point, axis_direction, angle, translation)`:
+    // a rotation by `angle` about the axis through `axis_point`, followed by a
+    // translation along the unit `axis_direction`. Pure translations report a
+    // zero angle with the translation direction as the axis.
+    pub fn to_screw(&self) -> (Vec3, Vec3, f32, f32) {
+        let Self { moment, direction } = *self;
+        let angle = direction.length();
+        if angle <= GEOMETRY_EPSILON {
+            return (Vec3::ZERO, moment.normalize_or_zero(), 0.0, moment.length());
+        }
+        let axis_direction = direction / angle;
+        let translation = axis_direction.dot(moment);
+        let axis_point = direction.cross(moment - axis_direction * translation) / (angle * angle);
+        (axis_point, axis_direction, angle, translation)
+    }
+
+    // Blends the lines themselves, so the interpolation happens in the Lie
+    // algebra rather than between the resulting rigid motions.
+    pub fn lerp(self, other: Pivot, t: f32) -> Pivot {
+        Self {
+            moment: self.moment * (1.0 - t) + other.moment * t,
+            direction: self.direction * (1.0 - t) + other.direction * t,
+        }
+    }
+
+    // The magnitude of the plane joining the point and the line.
+    fn distance(&self, point: Vec3) -> f32 {
+        (self.moment + self.direction.cross(point)).length()
     }
 
     fn scale(&self, alpha: f32) -> Self {
-        Self(self.0 * alpha)
+        Self {
+            moment: self.moment * alpha,
+            direction: self.direction * alpha,
+        }
     }
 }
 
 // https://rigidgeometricalgebra.org/wiki/index.php?title=Motor
 #[derive(Clone, Debug)]
-pub struct PivotalMotion {
+pub struct PivotalMotion<M = DefaultMotor> {
     pivots: Vec<Pivot>,
-    pre_motor: Motor,
-    post_motor: Motor,
+    pre_motor: M,
+    post_motor: M,
+    // Distances travelled along each pivot. They only depend on the pivots and
+    // the pre-motor, so global transforms and clones keep them.
+    pivot_distances: std::sync::OnceLock<Vec<f32>>,
+    distance_evaluation_counter: Counter,
 }
 
 impl PivotalMotion {
     pub fn from_pivots(pivots: Vec<Pivot>) -> Self {
+        Self::from_pivots_with(pivots)
+    }
+}
+
+impl<M: RigidMotor> PivotalMotion<M> {
+    // Like `from_pivots`, but composing in the given motor representation.
+    pub fn from_pivots_with(pivots: Vec<Pivot>) -> Self {
         Self {
             pivots,
-            pre_motor: Pivot::zero().as_motor(),
-            post_motor: Pivot::zero().as_motor(),
+            pre_motor: M::identity(),
+            post_motor: M::identity(),
+            pivot_distances: std::sync::OnceLock::new(),
+            distance_evaluation_counter: Counter::default(),
         }
     }
 
-    pub fn target(&self) -> Mat4 {
-        Self::matrix_from_motor(
+    // The same motion composed in another motor representation.
+    pub fn to_motor<N: RigidMotor>(&self) -> PivotalMotion<N> {
+        PivotalMotion {
+            pivots: self.pivots.clone(),
+            pre_motor: N::from_mat4(self.pre_motor.to_mat4()),
+            post_motor: N::from_mat4(self.post_motor.to_mat4()),
+            pivot_distances: self.pivot_distances.clone(),
+            distance_evaluation_counter: Counter::default(),
+        }
+    }
+
+    pub(crate) fn pivot_distances(&self) -> &[f32] {
+        self.pivot_distances.get_or_init(|| {
+            let point = self.pre_motor.to_mat4().transform_point3(Vec3::ZERO);
             self.pivots
                 .iter()
-                .fold(self.post_motor, |motor, pivot| {
-                    motor.geometric_product(pivot.as_motor())
+                .map(|pivot| {
+                    self.distance_evaluation_counter.increment();
+                    pivot.distance(point)
                 })
-                .geometric_product(self.pre_motor),
-        )
+                .collect()
+        })
+    }
+
+    // How many pivot distances this motion evaluated itself, rather than
+    // inheriting them from the motion it was derived from.
+    #[cfg(feature = "metrics")]
+    pub fn distance_evaluations(&self) -> usize {
+        self.distance_evaluation_counter.get()
+    }
+
+    pub fn target(&self) -> Mat4 {
+        self.pivots
+            .iter()
+            .fold(self.post_motor, |motor, &pivot| {
+                motor.compose(M::from_pivot(pivot))
+            })
+            .compose(self.pre_motor)
+            .to_mat4()
+    }
+
+    // Samples the pose a fraction `t` of the way along the motion, measured by
+    // the distance travelled as in `PivotalMotionTrajectory::consume_distance`.
+    // Pivots that move nothing are applied whole once `t` gets past them.
+    pub fn eval(&self, t: f32) -> Mat4 {
+        let t = t.clamp(0.0, 1.0);
+        let pivot_distances = self.pivot_distances();
+        let mut remaining_distance = t * pivot_distances.iter().sum::<f32>();
+        let motor = self.pivots.iter().zip(pivot_distances).fold(
+            self.post_motor,
+            |motor, (pivot, &distance)| {
+                let fraction = if distance > GEOMETRY_EPSILON {
+                    (remaining_distance / distance).clamp(0.0, 1.0)
+                } else if remaining_distance > 0.0 || t == 1.0 {
+                    1.0
+                } else {
+                    0.0
+                };
+                remaining_distance -= distance;
+                motor.compose(M::from_pivot(pivot.scale(fraction)))
+            },
+        );
+        motor.compose(self.pre_motor).to_mat4()
     }
 
     pub fn pivotal_local_transform(self, pivot: Pivot) -> Self {
         Self {
             pivots: self.pivots,
-            pre_motor: pivot.as_motor().geometric_product(self.pre_motor),
+            pre_motor: M::from_pivot(pivot).compose(self.pre_motor),
             post_motor: self.post_motor,
+            pivot_distances: std::sync::OnceLock::new(),
+            distance_evaluation_counter: Counter::default(),
         }
     }
 
@@ -88,10 +315,20 @@ impl PivotalMotion {
         Self {
             pivots: self.pivots,
             pre_motor: self.pre_motor,
-            post_motor: pivot.as_motor().geometric_product(self.post_motor),
+            post_motor: M::from_pivot(pivot).compose(self.post_motor),
+            pivot_distances: self.pivot_distances,
+            distance_evaluation_counter: Counter::default(),
         }
     }
 
+    pub fn translate_global(self, vector: Vec3) -> Self {
+        self.pivotal_global_transform(Pivot::from_translation_vector(vector))
+    }
+
+    pub fn rotate_global(self, matrix: Mat3) -> Self {
+        self.pivotal_global_transform(Pivot::from_rotation_matrix(matrix))
+    }
+
     pub fn rewind(self) -> Self {
         Self {
             pivots: self
@@ -101,77 +338,692 @@ impl PivotalMotion {
                 .map(|pivot| pivot.scale(-1.0))
                 .collect(),
             pre_motor: self.pre_motor,
-            post_motor: self.pivots.iter().fold(self.post_motor, |motor, pivot| {
-                motor.geometric_product(pivot.as_motor())
+            post_motor: self.pivots.iter().fold(self.post_motor, |motor, &pivot| {
+                motor.compose(M::from_pivot(pivot))
             }),
+            pivot_distances: std::sync::OnceLock::new(),
+            distance_evaluation_counter: Counter::default(),
         }
     }
+}
+
+// Maps the fraction of time elapsed to the fraction of distance covered.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    SmoothStep,
+}
 
-    fn matrix_from_motor(motor: Motor) -> Mat4 {
-        let x_axis = motor.transformation(Point::new(0.0, 1.0, 0.0, 0.0));
-        let y_axis = motor.transformation(Point::new(0.0, 0.0, 1.0, 0.0));
-        let z_axis = motor.transformation(Point::new(0.0, 0.0, 0.0, 1.0));
-        let w_axis = motor
-            .transformation(Point::new(1.0, 0.0, 0.0, 0.0))
-            .signum();
-        Mat4::from_cols_array_2d(&[
-            [x_axis[1], x_axis[2], x_axis[3], x_axis[0]],
-            [y_axis[1], y_axis[2], y_axis[3], y_axis[0]],
-            [z_axis[1], z_axis[2], z_axis[3], z_axis[0]],
-            [w_axis[1], w_axis[2], w_axis[3], w_axis[0]],
-        ])
+impl Easing {
+    fn apply(self, t: f32) -> f32 {
+        match self {
+            Self::Linear => t,
+            Self::SmoothStep => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MotionProfile {
+    pub max_speed: f32,
+    pub accel: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProfileState {
+    profile: MotionProfile,
+    speed: f32,
+}
+
+impl ProfileState {
+    pub fn new(profile: MotionProfile) -> Self {
+        Self {
+            profile,
+            speed: 0.0,
+        }
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
     }
 }
 
 #[derive(Clone, Debug)]
-pub struct PivotalMotionTrajectory(Vec<(Pivot, Motor, Motor, f32)>);
-
-impl PivotalMotionTrajectory {
-    pub fn from_pivotal_motions(pivotal_motions: Vec<PivotalMotion>) -> Self {
-        Self(
-            pivotal_motions
-                .into_iter()
-                .flat_map(|pivotal_motion| {
-                    let point = pivotal_motion
-                        .pre_motor
-                        .transformation(Point::new(1.0, 0.0, 0.0, 0.0))
-                        .signum();
-                    pivotal_motion.pivots.into_iter().scan(
-                        pivotal_motion.post_motor,
-                        move |motor_state, pivot| {
-                            let post_motor = *motor_state;
-                            let distance = pivot.distance(point);
-                            *motor_state = post_motor.geometric_product(pivot.as_motor());
-                            Some((
-                                pivot.scale(1.0 / distance),
-                                pivotal_motion.pre_motor,
-                                post_motor,
-                                distance,
-                            ))
-                        },
-                    )
-                })
-                .collect::<Vec<_>>()
-                .into_iter()
-                .rev()
-                .collect(),
+pub struct PivotalMotionTrajectory<M = DefaultMotor> {
+    segments: Vec<(Pivot, M, M, f32)>,
+    total_distance: f32,
+    easing: Easing,
+    // Time spent in `consume_time` so far.
+    elapsed_time: f32,
+}
+
+impl<M: RigidMotor> PivotalMotionTrajectory<M> {
+    pub fn from_pivotal_motions(pivotal_motions: Vec<PivotalMotion<M>>) -> Self {
+        let segments: Vec<_> = pivotal_motions
+            .into_iter()
+            .flat_map(|pivotal_motion| {
+                let pivot_distances = pivotal_motion.pivot_distances().to_vec();
+                pivotal_motion.pivots.into_iter().zip(pivot_distances).scan(
+                    pivotal_motion.post_motor,
+                    move |motor_state, (pivot, distance)| {
+                        let post_motor = *motor_state;
+                        *motor_state = post_motor.compose(M::from_pivot(pivot));
+                        Some((
+                            pivot.scale(1.0 / distance),
+                            pivotal_motion.pre_motor,
+                            post_motor,
+                            distance,
+                        ))
+                    },
+                )
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect();
+        let total_distance = segments.iter().map(|(_, _, _, distance)| distance).sum();
+        Self {
+            segments,
+            total_distance,
+            easing: Easing::Linear,
+            elapsed_time: 0.0,
+        }
+    }
+
+    pub fn then(mut self, other: Self) -> Self {
+        // Segments are stored in reverse order, so the other trajectory goes below.
+        self.segments.splice(0..0, other.segments);
+        self.total_distance += other.total_distance;
+        self
+    }
+
+    pub fn total_distance(&self) -> f32 {
+        self.total_distance
+    }
+
+    pub fn remaining_distance(&self) -> f32 {
+        self.segments
+            .iter()
+            .map(|(_, _, _, distance)| distance)
+            .sum()
+    }
+
+    pub fn progress(&self) -> f32 {
+        if self.total_distance > 0.0 {
+            (1.0 - self.remaining_distance() / self.total_distance).clamp(0.0, 1.0)
+        } else {
+            1.0
+        }
+    }
+
+    // Traces what is left of the trajectory from its end back to the current
+    // pose. Each segment starts from where it used to end and runs along the
+    // negated pivot, and the segment order flips.
+    pub fn reversed(self) -> Self {
+        let segments: Vec<_> = self
+            .segments
+            .into_iter()
+            .rev()
+            .map(|(pivot, pre_motor, post_motor, distance)| {
+                (
+                    pivot.scale(-1.0),
+                    pre_motor,
+                    post_motor.compose(M::from_pivot(pivot.scale(distance))),
+                    distance,
+                )
+            })
+            .collect();
+        let total_distance = segments.iter().map(|(_, _, _, distance)| distance).sum();
+        Self {
+            segments,
+            total_distance,
+            easing: self.easing,
+            elapsed_time: 0.0,
+        }
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    // At the given speed the whole trajectory takes `total_distance / speed`,
+    // with the easing spread over all of it rather than over each motion.
+    // Mixing this with the distance-based steps skews the timing.
+    pub fn consume_time(&mut self, dt: f32, speed: f32) -> Option<Mat4> {
+        if self.segments.is_empty() {
+            return None;
+        }
+        self.elapsed_time += dt;
+        let time_fraction = if self.total_distance > 0.0 && speed > 0.0 {
+            (self.elapsed_time * speed / self.total_distance).min(1.0)
+        } else {
+            1.0
+        };
+        if time_fraction >= 1.0 {
+            return self.consume_remaining_distance();
+        }
+        let consumed_distance = self.easing.apply(time_fraction) * self.total_distance
+            - (self.total_distance - self.remaining_distance());
+        self.consume_distance(consumed_distance.max(0.0))
+    }
+
+    // The pose the next step starts from, at the head of the current segment.
+    pub fn peek(&self) -> Option<Mat4> {
+        let (_, pre_motor, post_motor, _) = self.segments.last()?;
+        Some(post_motor.compose(*pre_motor).to_mat4())
+    }
+
+    // Advances along a trapezoidal velocity profile: accelerate up to the max
+    // speed, cruise, then brake so that the speed reaches zero at the very end.
+    pub fn consume_time_profiled(
+        &mut self,
+        dt: f32,
+        profile_state: &mut ProfileState,
+    ) -> Option<Mat4> {
+        let MotionProfile { max_speed, accel } = profile_state.profile;
+        let remaining_distance = self.remaining_distance();
+        let speed = (profile_state.speed + accel * dt)
+            .min(max_speed)
+            .min((2.0 * accel * remaining_distance).sqrt());
+        let consumed_distance = speed * dt;
+        if consumed_distance < remaining_distance {
+            profile_state.speed = speed;
+            self.consume_distance(consumed_distance)
+        } else {
+            profile_state.speed = 0.0;
+            self.consume_remaining_distance()
+        }
+    }
+
+    // Lands exactly on the end of the last segment, which `consume_distance`
+    // may overshoot due to rounding in the summed distances.
+    fn consume_remaining_distance(&mut self) -> Option<Mat4> {
+        let (pivot, pre_motor, post_motor, distance) = *self.segments.first()?;
+        self.segments.clear();
+        Some(
+            post_motor
+                .compose(M::from_pivot(pivot.scale(distance)))
+                .compose(pre_motor)
+                .to_mat4(),
         )
     }
 
     pub fn consume_distance(&mut self, consumed_distance: f32) -> Option<Mat4> {
-        let (pivot, pre_motor, post_motor, distance) = self.0.pop()?;
+        let (pivot, pre_motor, post_motor, distance) = self.segments.pop()?;
         (consumed_distance <= distance)
             .then(|| {
                 let next_post_motor =
-                    post_motor.geometric_product(pivot.scale(consumed_distance).as_motor());
-                self.0.push((
+                    post_motor.compose(M::from_pivot(pivot.scale(consumed_distance)));
+                self.segments.push((
                     pivot,
                     pre_motor,
                     next_post_motor,
                     distance - consumed_distance,
                 ));
-                PivotalMotion::matrix_from_motor(next_post_motor.geometric_product(pre_motor))
+                next_post_motor.compose(pre_motor).to_mat4()
             })
             .or_else(|| self.consume_distance(consumed_distance - distance))
     }
+
+    // Like `consume_distance`, but a step running past the end lands on the end
+    // and reports the distance it could not use.
+    pub fn consume_distance_tracked(&mut self, consumed_distance: f32) -> (Option<Mat4>, f32) {
+        let remaining_distance = self.remaining_distance();
+        if self.segments.is_empty() {
+            (None, consumed_distance)
+        } else if consumed_distance < remaining_distance {
+            (self.consume_distance(consumed_distance), 0.0)
+        } else {
+            (
+                self.consume_remaining_distance(),
+                consumed_distance - remaining_distance,
+            )
+        }
+    }
+}
+
+#[test]
+fn test_translate_global() {
+    let vector = Vec3::new(1.0, -2.0, 3.0);
+    let motion = PivotalMotion::from_pivots(Vec::from([Pivot::from_plucker(
+        std::f32::consts::FRAC_PI_2 * Vec3::X,
+        Vec3::Y,
+    )]));
+    let origin = motion.target().transform_point3(Vec3::ZERO);
+    let translated_origin = motion
+        .translate_global(vector)
+        .target()
+        .transform_point3(Vec3::ZERO);
+    assert!(translated_origin.abs_diff_eq(origin + vector, 1e-5));
+}
+
+#[test]
+fn test_eval() {
+    let motion = PivotalMotion::from_pivots(Vec::from([
+        Pivot::from_plucker(
+            std::f32::consts::FRAC_PI_2 * Vec3::Z,
+            Vec3::new(1.0, -1.0, 0.0),
+        ),
+        Pivot::from_plucker(
+            std::f32::consts::FRAC_PI_4 * Vec3::X,
+            Vec3::new(0.0, 1.0, 1.0),
+        ),
+        Pivot::from_plucker(
+            std::f32::consts::FRAC_PI_2 * Vec3::Z,
+            Vec3::new(-1.0, 1.0, 0.0),
+        ),
+    ]))
+    .pivotal_local_transform(Pivot::from_translation_vector(Vec3::X))
+    .translate_global(Vec3::new(0.0, 2.0, -1.0));
+    assert!(motion.eval(1.0).abs_diff_eq(motion.target(), 1e-5));
+    assert!(motion.eval(0.0).abs_diff_eq(
+        PivotalMotion::from_pivots(Vec::new())
+            .pivotal_local_transform(Pivot::from_translation_vector(Vec3::X))
+            .translate_global(Vec3::new(0.0, 2.0, -1.0))
+            .target(),
+        1e-5
+    ));
+    let mut trajectory = PivotalMotionTrajectory::from_pivotal_motions(Vec::from([motion.clone()]));
+    [0.2, 0.5, 0.9].into_iter().fold(0.0, |previous_t, t| {
+        let matrix = trajectory
+            .consume_distance((t - previous_t) * trajectory.total_distance())
+            .unwrap();
+        assert!(motion.eval(t).abs_diff_eq(matrix, 1e-4));
+        t
+    });
+}
+
+#[test]
+fn test_total_distance() {
+    let mut trajectory =
+        PivotalMotionTrajectory::from_pivotal_motions(Vec::from([PivotalMotion::from_pivots(
+            Vec::from([
+                Pivot::from_translation_vector(Vec3::Y),
+                Pivot::from_translation_vector(2.0 * Vec3::Z),
+            ]),
+        )]));
+    let total_distance = trajectory.total_distance();
+    assert!((total_distance - 3.0).abs() < 1e-5);
+    assert!((trajectory.remaining_distance() - total_distance).abs() < 1e-5);
+    let mut remaining_distance = trajectory.remaining_distance();
+    while trajectory.consume_distance(0.25).is_some() {
+        assert_eq!(trajectory.total_distance(), total_distance);
+        assert!(trajectory.remaining_distance() < remaining_distance);
+        remaining_distance = trajectory.remaining_distance();
+    }
+    assert!(trajectory.remaining_distance().abs() < 1e-5);
+}
+
+#[cfg(feature = "geometric_algebra")]
+#[test]
+fn test_glam_target() {
+    type Motor = geometric_algebra::ppga3d::Motor;
+
+    let pivots = Vec::from([
+        Pivot::from_translation_vector(Vec3::new(1.0, 2.0, 3.0)),
+        Pivot::from_plucker(Vec3::new(0.5, 0.2, -0.3), Vec3::new(1.0, -2.0, 0.5)),
+        Pivot::from_plucker(1.2 * Vec3::Z, Vec3::Y),
+    ]);
+    let glam_motion = PivotalMotion::<Mat4>::from_pivots_with(pivots.clone())
+        .pivotal_local_transform(Pivot::from_rotation_matrix(Mat3::from_rotation_x(0.3)))
+        .translate_global(Vec3::X);
+    let motor_motion = PivotalMotion::<Motor>::from_pivots_with(pivots)
+        .pivotal_local_transform(Pivot::from_rotation_matrix(Mat3::from_rotation_x(0.3)))
+        .translate_global(Vec3::X);
+    assert!(glam_motion
+        .target()
+        .abs_diff_eq(motor_motion.target(), 1e-5));
+    assert!(glam_motion
+        .to_motor::<Motor>()
+        .target()
+        .abs_diff_eq(motor_motion.target(), 1e-5));
+    [0.0, 0.3, 0.7, 1.0].into_iter().for_each(|t| {
+        assert!(glam_motion.eval(t).abs_diff_eq(motor_motion.eval(t), 1e-5));
+    });
+
+    let glam_trajectory = PivotalMotionTrajectory::from_pivotal_motions(Vec::from([
+        glam_motion.clone(),
+        glam_motion.clone().rewind(),
+    ]));
+    let motor_trajectory = PivotalMotionTrajectory::from_pivotal_motions(Vec::from([
+        motor_motion.clone(),
+        motor_motion.rewind(),
+    ]));
+    assert!((glam_trajectory.total_distance() - motor_trajectory.total_distance()).abs() < 1e-5);
+    let step = glam_trajectory.total_distance() / 10.0;
+    [
+        (glam_trajectory.clone(), motor_trajectory.clone()),
+        (glam_trajectory.clone().reversed(), motor_trajectory.clone().reversed()),
+    ]
+    .into_iter()
+    .for_each(|(mut glam_trajectory, mut motor_trajectory)| {
+        assert!(glam_trajectory
+            .peek()
+            .unwrap()
+            .abs_diff_eq(motor_trajectory.peek().unwrap(), 1e-5));
+        while let Some(glam_matrix) = glam_trajectory.consume_distance(step) {
+            assert!(glam_matrix.abs_diff_eq(motor_trajectory.consume_distance(step).unwrap(), 1e-4));
+        }
+    });
+    let (mut glam_trajectory, mut motor_trajectory) = (
+        glam_trajectory.with_easing(Easing::SmoothStep),
+        motor_trajectory.with_easing(Easing::SmoothStep),
+    );
+    while let Some(glam_matrix) = glam_trajectory.consume_time(0.1, 2.0) {
+        assert!(glam_matrix.abs_diff_eq(motor_trajectory.consume_time(0.1, 2.0).unwrap(), 1e-4));
+    }
+    assert!(motor_trajectory.consume_time(0.1, 2.0).is_none());
+}
+
+#[test]
+fn test_pivot_to_mat4() {
+    let vector = Vec3::new(1.0, -2.0, 3.0);
+    assert!(Pivot::from_translation_vector(vector)
+        .to_mat4()
+        .abs_diff_eq(Mat4::from_translation(vector), 1e-5));
+    let matrix = Mat3::from_axis_angle(Vec3::new(1.0, 2.0, -0.5).normalize(), 1.2);
+    let pivot_matrix = Pivot::from_rotation_matrix(matrix).to_mat4();
+    assert!(Mat3::from_mat4(pivot_matrix).abs_diff_eq(matrix, 1e-5));
+    assert!(pivot_matrix.w_axis.abs_diff_eq(glam::Vec4::W, 1e-5));
+    assert!(Pivot::zero().to_mat4().abs_diff_eq(Mat4::IDENTITY, 1e-6));
+}
+
+#[test]
+fn test_pivot_lerp() {
+    let pivot = Pivot::from_plucker(Vec3::new(0.5, 0.2, -0.3), Vec3::new(1.0, -2.0, 0.5));
+    let other_pivot = Pivot::from_translation_vector(Vec3::new(1.0, 2.0, 3.0));
+    assert!(pivot
+        .lerp(other_pivot, 0.0)
+        .to_mat4()
+        .abs_diff_eq(pivot.to_mat4(), 1e-6));
+    assert!(pivot
+        .lerp(other_pivot, 1.0)
+        .to_mat4()
+        .abs_diff_eq(other_pivot.to_mat4(), 1e-6));
+
+    let rotation = Pivot::from_rotation_matrix(Mat3::from_rotation_y(1.2));
+    let (axis_point, axis_direction, angle, translation) =
+        Pivot::zero().lerp(rotation, 0.25).to_screw();
+    assert!(axis_point.abs_diff_eq(Vec3::ZERO, 1e-6));
+    assert!(axis_direction.abs_diff_eq(Vec3::Y, 1e-6));
+    assert!((angle - 0.3).abs() < 1e-6);
+    assert!(translation.abs() < 1e-6);
+    assert!(Pivot::zero()
+        .lerp(rotation, 0.25)
+        .to_mat4()
+        .abs_diff_eq(Mat4::from_rotation_y(0.3), 1e-6));
+}
+
+#[test]
+fn test_consume_time_profiled() {
+    const DT: f32 = 0.01;
+    let profile = MotionProfile {
+        max_speed: 2.0,
+        accel: 1.0,
+    };
+    let mut trajectory =
+        PivotalMotionTrajectory::from_pivotal_motions(Vec::from([PivotalMotion::from_pivots(
+            Vec::from([
+                Pivot::from_translation_vector(4.0 * Vec3::Y),
+                Pivot::from_translation_vector(6.0 * Vec3::Z),
+            ]),
+        )]));
+    let mut profile_state = ProfileState::new(profile);
+    assert_eq!(profile_state.speed(), 0.0);
+    let mut speeds = Vec::new();
+    let mut last_transform = None;
+    while let Some(transform) = trajectory.consume_time_profiled(DT, &mut profile_state) {
+        speeds.push(profile_state.speed());
+        last_transform = Some(transform);
+    }
+    assert!(speeds[0] <= profile.accel * DT + 1e-6);
+    // Braking lags the ideal curve by a step, so the last few steps may shed a
+    // little more speed than the acceleration alone allows.
+    assert!(speeds
+        .windows(2)
+        .all(|pair| (pair[1] - pair[0]).abs() <= 3.0 * profile.accel * DT));
+    let cruise_index = speeds
+        .iter()
+        .position(|&speed| (speed - profile.max_speed).abs() < 1e-6)
+        .unwrap();
+    assert!(cruise_index > 0 && cruise_index < speeds.len() / 2);
+    assert_eq!(speeds.last(), Some(&0.0));
+    assert!(last_transform
+        .unwrap()
+        .transform_point3(Vec3::ZERO)
+        .abs_diff_eq(Vec3::new(0.0, 4.0, 6.0), 1e-4));
+    assert!(trajectory.remaining_distance().abs() < 1e-6);
+}
+
+#[test]
+fn test_consume_distance_tracked() {
+    let mut trajectory =
+        PivotalMotionTrajectory::from_pivotal_motions(Vec::from([PivotalMotion::from_pivots(
+            Vec::from([
+                Pivot::from_translation_vector(Vec3::Y),
+                Pivot::from_translation_vector(2.0 * Vec3::Z),
+            ]),
+        )]));
+    let (transform, leftover) = trajectory.consume_distance_tracked(1.5);
+    assert!(transform
+        .unwrap()
+        .transform_point3(Vec3::ZERO)
+        .abs_diff_eq(Vec3::new(0.0, 1.0, 0.5), 1e-5));
+    assert_eq!(leftover, 0.0);
+    let total_remaining = trajectory.remaining_distance();
+    let (transform, leftover) = trajectory.consume_distance_tracked(total_remaining + 0.25);
+    assert!(transform
+        .unwrap()
+        .transform_point3(Vec3::ZERO)
+        .abs_diff_eq(Vec3::new(0.0, 1.0, 2.0), 1e-5));
+    assert!((leftover - 0.25).abs() < 1e-6);
+    assert_eq!(trajectory.consume_distance_tracked(0.5), (None, 0.5));
+}
+
+#[test]
+fn test_progress() {
+    let mut trajectory =
+        PivotalMotionTrajectory::from_pivotal_motions(Vec::from([PivotalMotion::from_pivots(
+            Vec::from([
+                Pivot::from_translation_vector(Vec3::Y),
+                Pivot::from_translation_vector(2.0 * Vec3::Z),
+            ]),
+        )]));
+    assert_eq!(trajectory.progress(), 0.0);
+    trajectory.consume_distance(trajectory.total_distance() / 2.0);
+    assert!((trajectory.progress() - 0.5).abs() < 1e-5);
+    while trajectory.consume_distance(0.5).is_some() {}
+    assert_eq!(trajectory.progress(), 1.0);
+    assert_eq!(
+        PivotalMotionTrajectory::from_pivotal_motions(Vec::<PivotalMotion>::new()).progress(),
+        1.0
+    );
+}
+
+#[test]
+fn test_peek() {
+    let motion = PivotalMotion::from_pivots(Vec::from([
+        Pivot::from_translation_vector(Vec3::Y),
+        Pivot::from_translation_vector(2.0 * Vec3::Z),
+    ]))
+    .translate_global(Vec3::X);
+    let mut trajectory = PivotalMotionTrajectory::from_pivotal_motions(Vec::from([motion]));
+    assert!(trajectory
+        .peek()
+        .unwrap()
+        .abs_diff_eq(Mat4::from_translation(Vec3::X), 1e-6));
+    assert_eq!(trajectory.peek(), trajectory.peek());
+    assert!((trajectory.remaining_distance() - 3.0).abs() < 1e-6);
+    let matrix = trajectory.consume_distance(0.5).unwrap();
+    assert!(trajectory.peek().unwrap().abs_diff_eq(matrix, 1e-6));
+    assert!((trajectory.remaining_distance() - 2.5).abs() < 1e-6);
+    assert!((trajectory.total_distance() - 3.0).abs() < 1e-6);
+    trajectory.consume_distance(2.5);
+    assert!(trajectory.peek().is_some_and(
+        |matrix| matrix.abs_diff_eq(Mat4::from_translation(Vec3::new(1.0, 1.0, 2.0)), 1e-5)
+    ));
+    trajectory.consume_distance_tracked(1.0);
+    assert_eq!(trajectory.peek(), None);
+}
+
+#[test]
+fn test_consume_time() {
+    let trajectory = PivotalMotionTrajectory::from_pivotal_motions(Vec::from([
+        PivotalMotion::from_pivots(Vec::from([Pivot::from_translation_vector(Vec3::Y)])),
+        PivotalMotion::from_pivots(Vec::from([Pivot::from_translation_vector(2.0 * Vec3::Z)]))
+            .translate_global(Vec3::Y),
+    ]));
+    let end = Mat4::from_translation(Vec3::new(0.0, 1.0, 2.0));
+    [Easing::Linear, Easing::SmoothStep]
+        .into_iter()
+        .for_each(|easing| {
+            let mut trajectory = trajectory.clone().with_easing(easing);
+            let matrices: Vec<_> =
+                std::iter::from_fn(|| trajectory.consume_time(0.25, 2.0)).collect();
+            assert_eq!(matrices.len(), 6);
+            assert!(matrices[5].abs_diff_eq(end, 1e-5));
+            assert!(matrices[2].abs_diff_eq(
+                Mat4::from_translation(Vec3::new(0.0, 1.0, 0.5)),
+                1e-5
+            ));
+        });
+    let mut trajectory = trajectory.with_easing(Easing::SmoothStep);
+    let matrix = trajectory.consume_time(0.25, 2.0).unwrap();
+    assert!(matrix.w_axis.y < 0.5);
+}
+
+#[test]
+fn test_reversed() {
+    let pivots = [
+        Pivot::from_translation_vector(Vec3::Y),
+        Pivot::from_plucker(std::f32::consts::FRAC_PI_2 * Vec3::X, Vec3::Z),
+    ];
+    // The second motion starts where the first one ends.
+    let trajectory = PivotalMotionTrajectory::from_pivotal_motions(Vec::from([
+        PivotalMotion::from_pivots(Vec::from(pivots)),
+        PivotalMotion::from_pivots(Vec::from([Pivot::from_translation_vector(2.0 * Vec3::Z)]))
+            .pivotal_local_transform(pivots[1])
+            .pivotal_local_transform(pivots[0]),
+    ]));
+    let step_count = 12;
+    let step = trajectory.total_distance() / step_count as f32;
+    let mut forward_trajectory = trajectory.clone();
+    let mut poses = Vec::from([forward_trajectory.peek().unwrap()]);
+    poses.extend(
+        (0..step_count).map(|_| forward_trajectory.consume_distance_tracked(step).0.unwrap()),
+    );
+    let mut reversed_trajectory = trajectory.reversed();
+    let mut reversed_poses = Vec::from([reversed_trajectory.peek().unwrap()]);
+    reversed_poses.extend((0..step_count).map(|_| {
+        reversed_trajectory
+            .consume_distance_tracked(step)
+            .0
+            .unwrap()
+    }));
+    reversed_poses.reverse();
+    poses
+        .iter()
+        .zip(&reversed_poses)
+        .for_each(|(pose, reversed_pose)| assert!(pose.abs_diff_eq(*reversed_pose, 1e-4)));
+}
+
+#[test]
+fn test_pivot_distances_cache() {
+    fn segment_distances(trajectory: &PivotalMotionTrajectory) -> Vec<f32> {
+        trajectory
+            .segments
+            .iter()
+            .map(|(_, _, _, distance)| *distance)
+            .collect()
+    }
+
+    let pivotal_motion = PivotalMotion::from_pivots(Vec::from([
+        Pivot::from_translation_vector(Vec3::Y),
+        Pivot::from_plucker(std::f32::consts::FRAC_PI_2 * Vec3::X, Vec3::Z),
+    ]))
+    .pivotal_local_transform(Pivot::from_translation_vector(Vec3::X))
+    .translate_global(Vec3::new(1.0, 2.0, 3.0));
+    let cold_trajectory =
+        PivotalMotionTrajectory::from_pivotal_motions(Vec::from([pivotal_motion.clone()]));
+    pivotal_motion.pivot_distances();
+    let warm_trajectories: Vec<_> = (0..4)
+        .map(|_| {
+            PivotalMotionTrajectory::from_pivotal_motions(Vec::from([pivotal_motion
+                .clone()
+                .rotate_global(Mat3::from_rotation_z(1.0))]))
+        })
+        .collect();
+    warm_trajectories.iter().for_each(|warm_trajectory| {
+        assert_eq!(
+            segment_distances(warm_trajectory),
+            segment_distances(&cold_trajectory)
+        );
+    });
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+fn test_distance_evaluations() {
+    let pivotal_motion = PivotalMotion::from_pivots(Vec::from([
+        Pivot::from_translation_vector(Vec3::Y),
+        Pivot::from_plucker(std::f32::consts::FRAC_PI_2 * Vec3::X, Vec3::Z),
+    ]))
+    .pivotal_local_transform(Pivot::from_translation_vector(Vec3::X));
+    let cold_pivotal_motion = pivotal_motion
+        .clone()
+        .rotate_global(Mat3::from_rotation_z(1.0));
+    cold_pivotal_motion.pivot_distances();
+    assert_eq!(cold_pivotal_motion.distance_evaluations(), 2);
+    pivotal_motion.pivot_distances();
+    assert_eq!(pivotal_motion.distance_evaluations(), 2);
+    // Motions derived from a warmed motion evaluate no distances at all.
+    let warm_pivotal_motion = pivotal_motion
+        .clone()
+        .rotate_global(Mat3::from_rotation_z(1.0));
+    warm_pivotal_motion.pivot_distances();
+    assert_eq!(warm_pivotal_motion.distance_evaluations(), 0);
+    assert_eq!(
+        warm_pivotal_motion.pivot_distances(),
+        cold_pivotal_motion.pivot_distances()
+    );
+}
+
+#[test]
+fn test_from_screw() {
+    let axis_direction = Vec3::new(1.0, 2.0, -2.0).normalize();
+    let angle = 0.7;
+    assert!(Pivot::from_screw(Vec3::ZERO, axis_direction, angle, 0.0)
+        .to_mat4()
+        .abs_diff_eq(
+            Pivot::from_rotation_matrix(Mat3::from_axis_angle(axis_direction, angle)).to_mat4(),
+            1e-5
+        ));
+
+    let axis_point = Vec3::new(1.0, 0.0, 0.0);
+    let pitch = 2.0 / std::f32::consts::PI;
+    let motion = PivotalMotion::from_pivots(Vec::from([Pivot::from_screw(
+        axis_point,
+        Vec3::Z,
+        std::f32::consts::FRAC_PI_2,
+        pitch,
+    )]));
+    let expected_target = Mat4::from_translation(axis_point + Vec3::Z)
+        * Mat4::from_rotation_z(std::f32::consts::FRAC_PI_2)
+        * Mat4::from_translation(-axis_point);
+    assert!(motion.target().abs_diff_eq(expected_target, 1e-5));
+    assert!(motion
+        .target()
+        .transform_point3(Vec3::ZERO)
+        .abs_diff_eq(Vec3::new(1.0, -1.0, 1.0), 1e-5));
+
+    let (screw_axis_point, screw_axis_direction, screw_angle, translation) =
+        Pivot::from_screw(Vec3::new(0.5, -1.0, 3.0), axis_direction, angle, 0.3).to_screw();
+    assert!(screw_axis_direction.abs_diff_eq(axis_direction, 1e-5));
+    assert!((screw_angle - angle).abs() < 1e-5);
+    assert!((translation - 0.3 * angle).abs() < 1e-5);
+    assert!(screw_axis_point
+        .cross(axis_direction)
+        .abs_diff_eq(Vec3::new(0.5, -1.0, 3.0).cross(axis_direction), 1e-5));
 }
@@ -8,6 +8,136 @@ pub struct Polygon {
 }
 
 impl Polygon {
+    // Ear clipping, trying the ears in vertex order, so convex polygons come
+    // out as the fan around the first vertex. Triangles keep the winding of the
+    // polygon.
+    pub fn triangulate(&self) -> Vec<[Vec3; 3]> {
+        let winding_normal = self
+            .vertices
+            .iter()
+            .zip(self.vertices.iter().cycle().skip(1))
+            .map(|(vertex, next_vertex)| vertex.cross(*next_vertex))
+            .sum::<Vec3>();
+        let normal = if winding_normal.dot(self.normal) < 0.0 {
+            -self.normal
+        } else {
+            self.normal
+        };
+        let is_inside = |point: Vec3, [a, b, c]: [Vec3; 3]| {
+            [(a, b), (b, c), (c, a)]
+                .into_iter()
+                .all(|(start, end)| (end - start).cross(point - start).dot(normal) >= 0.0)
+        };
+        let mut vertices = self.vertices.clone();
+        let mut triangles = Vec::new();
+        while vertices.len() > 3 {
+            let ear_index = (1..vertices.len())
+                .find(|&index| {
+                    let triangle = [
+                        vertices[index - 1],
+                        vertices[index],
+                        vertices[(index + 1) % vertices.len()],
+                    ];
+                    let [a, b, c] = triangle;
+                    (b - a).cross(c - a).dot(normal) > 0.0
+                        && vertices
+                            .iter()
+                            .filter(|vertex| !triangle.contains(vertex))
+                            .all(|&vertex| !is_inside(vertex, triangle))
+                })
+                // Degenerate loops have no proper ear; clip anyway to terminate.
+                .unwrap_or(1);
+            triangles.push([
+                vertices[ear_index - 1],
+                vertices[ear_index],
+                vertices[(ear_index + 1) % vertices.len()],
+            ]);
+            vertices.remove(ear_index);
+        }
+        if let [a, b, c] = vertices[..] {
+            triangles.push([a, b, c]);
+        }
+        triangles
+    }
+
+    // Newell's method: the summed cross products of consecutive vertices give
+    // twice the area along the plane normal.
+    pub fn area(&self) -> f32 {
+        self.vertices
+            .iter()
+            .zip(self.vertices.iter().cycle().skip(1))
+            .map(|(vertex, next_vertex)| vertex.cross(*next_vertex))
+            .sum::<Vec3>()
+            .dot(self.normal.normalize())
+            .abs()
+            / 2.0
+    }
+
+    // Area-weighted over the fan around the first vertex, with signed areas so
+    // that concave outlines are handled as well. Degenerate polygons fall back
+    // to the vertex average.
+    pub fn centroid(&self) -> Vec3 {
+        let origin = self.vertices[0];
+        let (weighted_sum, total_area) = self
+            .vertices
+            .iter()
+            .skip(1)
+            .zip(self.vertices.iter().skip(2))
+            .map(|(&prev, &next)| {
+                let area = (prev - origin).cross(next - origin).dot(self.normal);
+                ((origin + prev + next) / 3.0 * area, area)
+            })
+            .fold((Vec3::ZERO, 0.0), |(sum, total), (weighted, area)| {
+                (sum + weighted, total + area)
+            });
+        if total_area.abs() > f32::EPSILON {
+            weighted_sum / total_area
+        } else {
+            self.vertices.iter().sum::<Vec3>() / self.vertices.len() as f32
+        }
+    }
+
+    // Each triangle of the fan is cut into `n * n` similar triangles, keeping
+    // the winding of the original. With `n == 0` the polygon comes back as is.
+    pub fn subdivide(self, n: u32) -> Polygons {
+        if n == 0 {
+            return Polygons(Vec::from([self]));
+        }
+        Polygons(
+            self.triangulate()
+                .into_iter()
+                .flat_map(|[a, b, c]| {
+                    let point = move |i: u32, j: u32| {
+                        a + (b - a) * (i as f32 / n as f32) + (c - a) * (j as f32 / n as f32)
+                    };
+                    (0..n).flat_map(move |i| {
+                        (0..n - i).flat_map(move |j| {
+                            std::iter::once([point(i, j), point(i + 1, j), point(i, j + 1)]).chain(
+                                (i + j + 1 < n).then(|| {
+                                    [point(i + 1, j), point(i + 1, j + 1), point(i, j + 1)]
+                                }),
+                            )
+                        })
+                    })
+                })
+                .map(|vertices| Polygon {
+                    vertices: Vec::from(vertices),
+                    normal: self.normal,
+                })
+                .collect(),
+        )
+    }
+
+    pub fn transform_into(&self, matrix: Mat4, out: &mut Polygon) {
+        out.vertices.clear();
+        out.vertices.extend(
+            self.vertices
+                .iter()
+                .map(|&vertex| matrix.transform_point3(vertex)),
+        );
+        out.normal = matrix.transform_vector3(self.normal);
+    }
+
     fn transform(self, matrix: Mat4) -> Self {
         Self {
             vertices: self
@@ -20,10 +150,47 @@ impl Polygon {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct Polygons(pub Vec<Polygon>);
 
 impl Polygons {
+    pub fn triangulate(&self) -> Vec<[Vec3; 3]> {
+        self.0.iter().flat_map(Polygon::triangulate).collect()
+    }
+
+    pub fn aabb(&self) -> (Vec3, Vec3) {
+        self.0
+            .iter()
+            .flat_map(|polygon| polygon.vertices.iter())
+            .fold(
+                (Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY)),
+                |(min, max), &vertex| (min.min(vertex), max.max(vertex)),
+            )
+    }
+
+    pub fn transform_into(&self, matrix: Mat4, out: &mut Polygons) {
+        let end = self.transform_into_at(matrix, out, 0);
+        out.0.truncate(end);
+    }
+
+    // Writes the transformed polygons over `out` from `start` on, growing it
+    // when needed, and returns the index after the last one written.
+    pub(crate) fn transform_into_at(
+        &self,
+        matrix: Mat4,
+        out: &mut Polygons,
+        start: usize,
+    ) -> usize {
+        self.0
+            .iter()
+            .enumerate()
+            .for_each(|(index, polygon)| match out.0.get_mut(start + index) {
+                Some(out_polygon) => polygon.transform_into(matrix, out_polygon),
+                None => out.0.push(polygon.clone().transform(matrix)),
+            });
+        start + self.0.len()
+    }
+
     pub fn transform(self, transform: Mat4) -> Self {
         Self(
             self.0
@@ -32,6 +199,117 @@ impl Polygons {
                 .collect(),
         )
     }
+
+    // Vertices closer than `epsilon` to an already kept vertex are merged into
+    // it, so the first occurrence decides the welded position.
+    pub fn weld(self, epsilon: f32) -> (Vec<Vec3>, Vec<Vec<u32>>) {
+        let mut vertices: Vec<Vec3> = Vec::new();
+        let indices = self
+            .0
+            .into_iter()
+            .map(|polygon| {
+                polygon
+                    .vertices
+                    .into_iter()
+                    .map(|vertex| {
+                        vertices
+                            .iter()
+                            .position(|welded_vertex| welded_vertex.distance(vertex) < epsilon)
+                            .unwrap_or_else(|| {
+                                vertices.push(vertex);
+                                vertices.len() - 1
+                            }) as u32
+                    })
+                    .collect()
+            })
+            .collect();
+        (vertices, indices)
+    }
+
+    // `light_dir` points from the light into the scene; polygons facing away
+    // from the light come out black.
+    pub fn shaded(&self, light_dir: Vec3, base: [f32; 3]) -> Vec<[f32; 3]> {
+        let light_dir = light_dir.normalize_or_zero();
+        self.0
+            .iter()
+            .map(|polygon| {
+                let intensity = polygon.normal.normalize_or_zero().dot(-light_dir).max(0.0);
+                base.map(|component| (component * intensity).clamp(0.0, 1.0))
+            })
+            .collect()
+    }
+
+    // `view_direction` points from the scene towards the viewer.
+    pub fn partition_by_facing(self, view_direction: Vec3) -> (Self, Self) {
+        let (front_polygons, back_polygons) = self
+            .0
+            .into_iter()
+            .partition(|polygon| polygon.normal.dot(view_direction) > 0.0);
+        (Self(front_polygons), Self(back_polygons))
+    }
+}
+
+// The frame traces the three back edges of the tile cube as strips of the
+// given thickness, centered on the edges.
+pub fn frame_polygons(thickness: f32) -> Polygons {
+    let outer = 1.0 + thickness / 2.0;
+    let inner = 1.0 - thickness / 2.0;
+    Polygons(Vec::from([
+        Polygon {
+            vertices: Vec::from([
+                Vec3::new(-1.0, outer, -1.0),
+                Vec3::new(-1.0, outer, outer),
+                Vec3::new(-1.0, inner, outer),
+                Vec3::new(-1.0, inner, -1.0),
+            ]),
+            normal: Vec3::new(1.0, 0.0, 0.0),
+        },
+        Polygon {
+            vertices: Vec::from([
+                Vec3::new(-1.0, outer, outer),
+                Vec3::new(-1.0, -1.0, outer),
+                Vec3::new(-1.0, -1.0, inner),
+                Vec3::new(-1.0, outer, inner),
+            ]),
+            normal: Vec3::new(1.0, 0.0, 0.0),
+        },
+        Polygon {
+            vertices: Vec::from([
+                Vec3::new(outer, -1.0, -1.0),
+                Vec3::new(outer, -1.0, outer),
+                Vec3::new(inner, -1.0, outer),
+                Vec3::new(inner, -1.0, -1.0),
+            ]),
+            normal: Vec3::new(0.0, 1.0, 0.0),
+        },
+        Polygon {
+            vertices: Vec::from([
+                Vec3::new(outer, -1.0, outer),
+                Vec3::new(-1.0, -1.0, outer),
+                Vec3::new(-1.0, -1.0, inner),
+                Vec3::new(outer, -1.0, inner),
+            ]),
+            normal: Vec3::new(0.0, 1.0, 0.0),
+        },
+        Polygon {
+            vertices: Vec::from([
+                Vec3::new(outer, -1.0, -1.0),
+                Vec3::new(outer, outer, -1.0),
+                Vec3::new(inner, outer, -1.0),
+                Vec3::new(inner, -1.0, -1.0),
+            ]),
+            normal: Vec3::new(0.0, 0.0, 1.0),
+        },
+        Polygon {
+            vertices: Vec::from([
+                Vec3::new(outer, outer, -1.0),
+                Vec3::new(-1.0, outer, -1.0),
+                Vec3::new(-1.0, inner, -1.0),
+                Vec3::new(outer, inner, -1.0),
+            ]),
+            normal: Vec3::new(0.0, 0.0, 1.0),
+        },
+    ]))
 }
 
 lazy_static::lazy_static! {
@@ -118,60 +396,225 @@ lazy_static::lazy_static! {
             normal: Vec3::new(0.0, 0.0, 1.0),
         },
     ]));
-    pub static ref FRAME_POLYGONS: Polygons = Polygons(Vec::from([
-        Polygon {
-            vertices: Vec::from([
-                Vec3::new(-1.0, 1.025, -1.0),
-                Vec3::new(-1.0, 1.025, 1.025),
-                Vec3::new(-1.0, 0.975, 1.025),
-                Vec3::new(-1.0, 0.975, -1.0),
-            ]),
-            normal: Vec3::new(1.0, 0.0, 0.0),
-        },
-        Polygon {
-            vertices: Vec::from([
-                Vec3::new(-1.0, 1.025, 1.025),
-                Vec3::new(-1.0, -1.0, 1.025),
-                Vec3::new(-1.0, -1.0, 0.975),
-                Vec3::new(-1.0, 1.025, 0.975),
-            ]),
-            normal: Vec3::new(1.0, 0.0, 0.0),
-        },
-        Polygon {
-            vertices: Vec::from([
-                Vec3::new(1.025, -1.0, -1.0),
-                Vec3::new(1.025, -1.0, 1.025),
-                Vec3::new(0.975, -1.0, 1.025),
-                Vec3::new(0.975, -1.0, -1.0),
-            ]),
-            normal: Vec3::new(0.0, 1.0, 0.0),
-        },
-        Polygon {
-            vertices: Vec::from([
-                Vec3::new(1.025, -1.0, 1.025),
-                Vec3::new(-1.0, -1.0, 1.025),
-                Vec3::new(-1.0, -1.0, 0.975),
-                Vec3::new(1.025, -1.0, 0.975),
-            ]),
-            normal: Vec3::new(0.0, 1.0, 0.0),
-        },
-        Polygon {
-            vertices: Vec::from([
-                Vec3::new(1.025, -1.0, -1.0),
-                Vec3::new(1.025, 1.025, -1.0),
-                Vec3::new(0.975, 1.025, -1.0),
-                Vec3::new(0.975, -1.0, -1.0),
-            ]),
-            normal: Vec3::new(0.0, 0.0, 1.0),
-        },
-        Polygon {
-            vertices: Vec::from([
-                Vec3::new(1.025, 1.025, -1.0),
-                Vec3::new(-1.0, 1.025, -1.0),
-                Vec3::new(-1.0, 0.975, -1.0),
-                Vec3::new(1.025, 0.975, -1.0),
-            ]),
-            normal: Vec3::new(0.0, 0.0, 1.0),
-        },
+    pub static ref FRAME_POLYGONS: Polygons = frame_polygons(0.05);
+}
+
+#[test]
+fn test_partition_by_facing() {
+    let (front_polygons, back_polygons) = PLAYER_POLYGONS.clone().partition_by_facing(Vec3::ONE);
+    assert_eq!(
+        front_polygons.0.len() + back_polygons.0.len(),
+        PLAYER_POLYGONS.0.len()
+    );
+    assert!(front_polygons
+        .0
+        .iter()
+        .any(|polygon| polygon.vertices.len() == 3));
+    assert!(back_polygons
+        .0
+        .iter()
+        .all(|polygon| polygon.vertices.len() != 3));
+    let (front_polygons, back_polygons) =
+        PLAYER_POLYGONS.clone().partition_by_facing(Vec3::NEG_ONE);
+    assert!(front_polygons.0.is_empty());
+    assert_eq!(back_polygons.0.len(), PLAYER_POLYGONS.0.len());
+}
+
+#[test]
+fn test_subdivide() {
+    fn area(polygons: &Polygons) -> f32 {
+        polygons
+            .0
+            .iter()
+            .flat_map(Polygon::triangulate)
+            .map(|[a, b, c]| (b - a).cross(c - a).length() / 2.0)
+            .sum()
+    }
+
+    let quad = MARKER_POLYGONS.0[0].clone();
+    let quad_area = area(&Polygons(Vec::from([quad.clone()])));
+    let polygons = quad.clone().subdivide(0);
+    assert_eq!(polygons.0.len(), 1);
+    assert_eq!(polygons.0[0].vertices, quad.vertices);
+    let polygons = quad.clone().subdivide(1);
+    assert_eq!(polygons.0.len(), 2);
+    assert!((area(&polygons) - quad_area).abs() < 1e-5);
+    let polygons = quad.clone().subdivide(2);
+    assert_eq!(polygons.0.len(), 8);
+    assert!((area(&polygons) - quad_area).abs() < 1e-5);
+    assert!(polygons.0.iter().all(|polygon| {
+        let [a, b, c] = [
+            polygon.vertices[0],
+            polygon.vertices[1],
+            polygon.vertices[2],
+        ];
+        polygon.normal == quad.normal && (b - a).cross(c - a).dot(quad.normal) > 0.0
+    }));
+}
+
+#[test]
+fn test_weld() {
+    use super::fragment::TileFragment;
+    use super::fragment::POLYGONS_DICT;
+
+    let mut quads = POLYGONS_DICT[&TileFragment::ArchMajorFace].0[..2].to_vec();
+    quads[1].vertices[0] += Vec3::splat(1e-6);
+    let (vertices, indices) = Polygons(quads.clone()).weld(1e-4);
+    assert_eq!(vertices.len(), 6);
+    assert_eq!(indices.len(), 2);
+    let shared_indices: Vec<_> = indices[0]
+        .iter()
+        .filter(|index| indices[1].contains(index))
+        .collect();
+    assert_eq!(shared_indices.len(), 2);
+    indices
+        .iter()
+        .zip(&quads)
+        .for_each(|(polygon_indices, quad)| {
+            polygon_indices
+                .iter()
+                .zip(&quad.vertices)
+                .for_each(|(&index, vertex)| {
+                    assert!(vertices[index as usize].abs_diff_eq(*vertex, 1e-4))
+                });
+        });
+    assert_eq!(Polygons(quads).weld(0.0).0.len(), 8);
+}
+
+#[test]
+fn test_shaded() {
+    let colors = PLAYER_POLYGONS.shaded(Vec3::new(-1.0, -0.5, -0.2), [0.8, 0.6, 1.0]);
+    assert_eq!(colors.len(), PLAYER_POLYGONS.0.len());
+    assert!(colors
+        .iter()
+        .flatten()
+        .all(|component| (0.0..=1.0).contains(component)));
+    let polygons = Polygons(Vec::from([
+        MARKER_POLYGONS.0[0].clone(),
+        MARKER_POLYGONS.0[0]
+            .clone()
+            .transform(Mat4::from_rotation_x(std::f32::consts::PI)),
     ]));
+    let colors = polygons.shaded(Vec3::NEG_Z, [1.0, 1.0, 1.0]);
+    assert!(colors[0][0] > colors[1][0]);
+    assert_eq!(colors[1], [0.0; 3]);
+}
+
+#[test]
+fn test_triangulate() {
+    use super::fragment::TileFragment;
+    use super::fragment::POLYGONS_DICT;
+
+    fn area(triangles: &[[Vec3; 3]], normal: Vec3) -> f32 {
+        triangles
+            .iter()
+            .map(|[a, b, c]| (b - a).cross(c - a).dot(normal) / 2.0)
+            .sum()
+    }
+
+    let quad = &MARKER_POLYGONS.0[0];
+    let triangles = quad.triangulate();
+    assert_eq!(triangles.len(), 2);
+    assert!((area(&triangles, quad.normal) - 1.44).abs() < 1e-5);
+
+    let arch_face = &POLYGONS_DICT[&TileFragment::ArchMajorFace];
+    assert_eq!(
+        arch_face.triangulate().len(),
+        arch_face
+            .0
+            .iter()
+            .map(|polygon| polygon.vertices.len() - 2)
+            .sum::<usize>()
+    );
+
+    // An L shape, whose fan around the first vertex would leave the outline.
+    let l_shape = Polygon {
+        vertices: Vec::from([
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(2.0, 2.0, 0.0),
+            Vec3::new(1.0, 2.0, 0.0),
+        ]),
+        normal: Vec3::Z,
+    };
+    let triangles = l_shape.triangulate();
+    assert_eq!(triangles.len(), 4);
+    assert!((area(&triangles, Vec3::Z) - 3.0).abs() < 1e-5);
+    assert!(triangles
+        .iter()
+        .all(|[a, b, c]| (b - a).cross(c - a).dot(Vec3::Z) > 0.0));
+}
+
+#[test]
+fn test_area_centroid_aabb() {
+    let triangle = Polygon {
+        vertices: Vec::from([
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(3.0, 0.0, 1.0),
+            Vec3::new(0.0, 3.0, 1.0),
+        ]),
+        normal: Vec3::Z,
+    };
+    assert!((triangle.area() - 4.5).abs() < 1e-5);
+    assert!(triangle
+        .centroid()
+        .abs_diff_eq(Vec3::new(1.0, 1.0, 1.0), 1e-5));
+
+    // A trapezoid, whose vertex average differs from its centroid.
+    let quad = Polygon {
+        vertices: Vec::from([
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(4.0, 0.0, 0.0),
+            Vec3::new(3.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 1.0),
+        ]),
+        normal: Vec3::NEG_Y,
+    };
+    assert!((quad.area() - 3.0).abs() < 1e-5);
+    assert!(quad
+        .centroid()
+        .abs_diff_eq(Vec3::new(2.0, 0.0, 4.0 / 9.0), 1e-5));
+
+    let polygons = Polygons(Vec::from([triangle, quad]));
+    let (min, max) = polygons.aabb();
+    assert_eq!(min, Vec3::ZERO);
+    assert_eq!(max, Vec3::new(4.0, 3.0, 1.0));
+}
+
+#[test]
+fn test_transform_into() {
+    use super::fragment::TileFragment;
+    use super::fragment::POLYGONS_DICT;
+
+    fn assert_same(polygons: &Polygons, expected_polygons: &Polygons) {
+        assert_eq!(polygons.0.len(), expected_polygons.0.len());
+        polygons
+            .0
+            .iter()
+            .zip(&expected_polygons.0)
+            .for_each(|(polygon, expected_polygon)| {
+                assert_eq!(polygon.vertices, expected_polygon.vertices);
+                assert_eq!(polygon.normal, expected_polygon.normal);
+            });
+    }
+
+    let matrix = Mat4::from_translation(Vec3::new(1.0, -2.0, 3.0))
+        * Mat4::from_rotation_y(0.3)
+        * Mat4::from_scale(Vec3::splat(1.5));
+    let mut out = Polygons(Vec::new());
+    // Growing from empty, shrinking onto the larger buffer, and growing again.
+    [
+        TileFragment::ArchMajorFace,
+        TileFragment::TriangleXFore,
+        TileFragment::LadderMinorBulkSide,
+        TileFragment::ArchMinorFace,
+    ]
+    .into_iter()
+    .for_each(|tile_fragment| {
+        let polygons = &POLYGONS_DICT[&tile_fragment];
+        polygons.transform_into(matrix, &mut out);
+        assert_same(&out, &polygons.clone().transform(matrix));
+    });
 }